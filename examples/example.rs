@@ -1,4 +1,4 @@
-use ansi_escape_codes::{ASCIIControlCode, ControlSequencePart, ControlSequenceInducerParameter, SelectGraphicRenditionParameter, ForegroundColor, Color, EscapeSequence, BackgroundColor, BrightForegroundColor };
+use ansi_escape_codes::{ASCIIControlCode, SelectGraphicRenditionParameter, ForegroundColor, Color, EscapeSequence, BackgroundColor, BrightForegroundColor };
 
 fn main() {
     let params1: Vec<SelectGraphicRenditionParameter> = vec![
@@ -39,10 +39,10 @@ fn main() {
     ];
     println!(
         "{}test{}test2!{}test3{}",
-        EscapeSequence::SelectGraphicRenditionSequence(params1).to_string(),
-        EscapeSequence::ResetColorsSequence.to_string(),
-        EscapeSequence::SelectGraphicRenditionSequence(params2).to_string(),
-        ASCIIControlCode::CarriageReturnCode.to_string()
+        EscapeSequence::SelectGraphicRenditionSequence(params1),
+        EscapeSequence::ResetColorsSequence,
+        EscapeSequence::SelectGraphicRenditionSequence(params2),
+        ASCIIControlCode::CarriageReturnCode
     );
     //println!("\x1b[55;2mwoo");
 }
@@ -0,0 +1,531 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsciicastHeader {
+    pub width: u32,
+    pub height: u32,
+    pub timestamp: Option<u64>, // seconds since the Unix epoch
+    pub title: Option<String>,
+    pub env: Vec<(String, String)>,
+}
+
+impl AsciicastHeader {
+    pub fn new(width: u32, height: u32) -> AsciicastHeader {
+        AsciicastHeader {
+            width,
+            height,
+            timestamp: None,
+            title: None,
+            env: Vec::new(),
+        }
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> AsciicastHeader {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn with_title(mut self, title: &str) -> AsciicastHeader {
+        self.title = Some(String::from(title));
+        self
+    }
+
+    // Players commonly look at SHELL and TERM.
+    pub fn with_env(mut self, name: &str, value: &str) -> AsciicastHeader {
+        self.env.push((String::from(name), String::from(value)));
+        self
+    }
+
+    pub fn parse(line: &str) -> Option<AsciicastHeader> {
+        let fields = match JsonValue::parse(line)? {
+            JsonValue::Object(fields) => fields,
+            _ => return None,
+        };
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value)
+        };
+        if field("version")?.as_number()? != 2.0 {
+            return None;
+        }
+        let mut header = AsciicastHeader::new(
+            field("width")?.as_number()? as u32,
+            field("height")?.as_number()? as u32,
+        );
+        header.timestamp = field("timestamp")
+            .and_then(JsonValue::as_number)
+            .map(|timestamp| timestamp as u64);
+        header.title = field("title").and_then(JsonValue::as_str).map(String::from);
+        if let Some(JsonValue::Object(env)) = field("env") {
+            header.env = env
+                .iter()
+                .filter_map(|(name, value)| Some((name.clone(), String::from(value.as_str()?))))
+                .collect();
+        }
+        Some(header)
+    }
+}
+
+impl fmt::Display for AsciicastHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut fields = vec![
+            String::from("\"version\": 2"),
+            format!("\"width\": {}", self.width),
+            format!("\"height\": {}", self.height),
+        ];
+        if let Some(timestamp) = self.timestamp {
+            fields.push(format!("\"timestamp\": {}", timestamp));
+        }
+        if let Some(title) = &self.title {
+            fields.push(format!("\"title\": {}", json_string(title)));
+        }
+        if !self.env.is_empty() {
+            let env: Vec<String> = self
+                .env
+                .iter()
+                .map(|(name, value)| format!("{}: {}", json_string(name), json_string(value)))
+                .collect();
+            fields.push(format!("\"env\": {{{}}}", env.join(", ")));
+        }
+        write!(f, "{{{}}}", fields.join(", "))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsciicastEventCode {
+    OutputCode,
+    InputCode,
+    MarkerCode,
+    ResizeCode, // data is "COLUMNSxROWS"
+}
+
+impl AsciicastEventCode {
+    fn parse(code: &str) -> Option<AsciicastEventCode> {
+        match code {
+            "o" => Some(AsciicastEventCode::OutputCode),
+            "i" => Some(AsciicastEventCode::InputCode),
+            "m" => Some(AsciicastEventCode::MarkerCode),
+            "r" => Some(AsciicastEventCode::ResizeCode),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AsciicastEventCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            AsciicastEventCode::OutputCode => String::from("o"),
+            AsciicastEventCode::InputCode => String::from("i"),
+            AsciicastEventCode::MarkerCode => String::from("m"),
+            AsciicastEventCode::ResizeCode => String::from("r"),
+        };
+        f.write_str(&text)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsciicastEvent {
+    pub time: f64, // seconds since the start of the recording
+    pub code: AsciicastEventCode,
+    pub data: String,
+}
+
+impl AsciicastEvent {
+    pub fn parse(line: &str) -> Option<AsciicastEvent> {
+        match JsonValue::parse(line)? {
+            JsonValue::Array(values) if values.len() == 3 => Some(AsciicastEvent {
+                time: values[0].as_number()?,
+                code: AsciicastEventCode::parse(values[1].as_str()?)?,
+                data: String::from(values[2].as_str()?),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AsciicastEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{:.6}, \"{}\", {}]",
+            self.time,
+            self.code,
+            json_string(&self.data)
+        )
+    }
+}
+
+// Records everything written to it as output events of an asciicast v2 file written to the
+// inner writer. Timestamps count from when the recorder was created.
+pub struct Recorder<W: Write> {
+    writer: Option<W>, // only taken by into_inner
+    start: Instant,
+    // The tail of a UTF-8 character split across two writes.
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(mut writer: W, header: AsciicastHeader) -> io::Result<Recorder<W>> {
+        writeln!(writer, "{}", header)?;
+        Ok(Recorder {
+            writer: Some(writer),
+            start: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn record_input(&mut self, data: &str) -> io::Result<()> {
+        self.record(AsciicastEventCode::InputCode, data)
+    }
+
+    // Markers show up as chapter points in players.
+    pub fn record_marker(&mut self, label: &str) -> io::Result<()> {
+        self.record(AsciicastEventCode::MarkerCode, label)
+    }
+
+    pub fn record_resize(&mut self, width: u32, height: u32) -> io::Result<()> {
+        self.record(
+            AsciicastEventCode::ResizeCode,
+            &format!("{}x{}", width, height),
+        )
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer
+            .as_mut()
+            .expect("writer taken before the recorder was dropped")
+    }
+
+    // Incomplete UTF-8 left over from the last write is recorded with replacement characters,
+    // as it is when the recorder is dropped, where errors are ignored.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.record_pending()?;
+        Ok(self
+            .writer
+            .take()
+            .expect("writer taken before the recorder was dropped"))
+    }
+
+    fn record_pending(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let data = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        self.record(AsciicastEventCode::OutputCode, &data)
+    }
+
+    fn record(&mut self, code: AsciicastEventCode, data: &str) -> io::Result<()> {
+        let event = AsciicastEvent {
+            time: self.start.elapsed().as_secs_f64(),
+            code,
+            data: String::from(data),
+        };
+        writeln!(self.get_mut(), "{}", event)
+    }
+}
+
+impl<W: Write> Drop for Recorder<W> {
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.record_pending();
+        }
+    }
+}
+
+impl<W: Write> Write for Recorder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let complete = match std::str::from_utf8(&self.pending) {
+            // Keep a character cut off at the end for the next write.
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            _ => self.pending.len(),
+        };
+        let bytes: Vec<u8> = self.pending.drain(..complete).collect();
+        if !bytes.is_empty() {
+            let data = String::from_utf8_lossy(&bytes).into_owned();
+            self.record(AsciicastEventCode::OutputCode, &data)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.get_mut().flush()
+    }
+}
+
+// Replays the output events of an asciicast v2 file.
+pub struct Player {
+    header: AsciicastHeader,
+    events: Vec<AsciicastEvent>,
+    speed: f64,
+    idle_time_limit: Option<f64>,
+}
+
+impl Player {
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Player> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = reader.lines();
+        let header = match lines.next() {
+            Some(line) => AsciicastHeader::parse(&line?)
+                .ok_or_else(|| invalid("not an asciicast v2 header"))?,
+            None => return Err(invalid("empty asciicast file")),
+        };
+        let mut events = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(AsciicastEvent::parse(&line).ok_or_else(|| invalid("malformed event"))?);
+        }
+        Ok(Player {
+            header,
+            events,
+            speed: 1.0,
+            idle_time_limit: None,
+        })
+    }
+
+    // 2.0 plays twice as fast, 0.5 at half speed. Speeds that are not finite and positive are
+    // ignored.
+    pub fn with_speed(mut self, speed: f64) -> Player {
+        if speed.is_finite() && speed > 0.0 {
+            self.speed = speed;
+        }
+        self
+    }
+
+    // Pauses longer than this many seconds (before scaling) are shortened to it. Negative
+    // limits are ignored.
+    pub fn with_idle_time_limit(mut self, idle_time_limit: f64) -> Player {
+        if idle_time_limit >= 0.0 {
+            self.idle_time_limit = Some(idle_time_limit);
+        }
+        self
+    }
+
+    pub fn header(&self) -> &AsciicastHeader {
+        &self.header
+    }
+
+    pub fn events(&self) -> &[AsciicastEvent] {
+        &self.events
+    }
+
+    // Writes each output event once its time has come, sleeping in between. An event too far
+    // in the future to wait for is an error.
+    pub fn play<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let start = Instant::now();
+        let mut previous_time = 0.0;
+        let mut elapsed = 0.0;
+        for event in &self.events {
+            let mut pause = (event.time - previous_time).max(0.0);
+            if let Some(idle_time_limit) = self.idle_time_limit {
+                pause = pause.min(idle_time_limit);
+            }
+            previous_time = event.time;
+            elapsed += pause;
+            if event.code != AsciicastEventCode::OutputCode {
+                continue;
+            }
+            let due = Duration::try_from_secs_f64(elapsed / self.speed).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "event time out of range")
+            })?;
+            if let Some(wait) = due.checked_sub(start.elapsed()) {
+                thread::sleep(wait);
+            }
+            writer.write_all(event.data.as_bytes())?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\x7f' => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Arrays and objects nested deeper than this are rejected, so that hostile input cannot
+// overflow the stack.
+const MAXIMUM_JSON_DEPTH: usize = 128;
+
+// Just enough JSON to read asciicast files.
+enum JsonValue {
+    Literal, // true, false or null, none of which asciicast needs the value of
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn parse(text: &str) -> Option<JsonValue> {
+        let chars: Vec<char> = text.chars().collect();
+        let (value, index) = JsonValue::parse_value(&chars, 0, 0)?;
+        if skip_whitespace(&chars, index) == chars.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn parse_value(chars: &[char], index: usize, depth: usize) -> Option<(JsonValue, usize)> {
+        let index = skip_whitespace(chars, index);
+        match chars.get(index)? {
+            '{' | '[' if depth == MAXIMUM_JSON_DEPTH => None,
+            '{' => {
+                let mut fields = Vec::new();
+                let mut index = skip_whitespace(chars, index + 1);
+                if chars.get(index) == Some(&'}') {
+                    return Some((JsonValue::Object(fields), index + 1));
+                }
+                loop {
+                    let (key, next) = parse_string(chars, skip_whitespace(chars, index))?;
+                    let next = skip_whitespace(chars, next);
+                    if chars.get(next) != Some(&':') {
+                        return None;
+                    }
+                    let (value, next) = JsonValue::parse_value(chars, next + 1, depth + 1)?;
+                    fields.push((key, value));
+                    index = skip_whitespace(chars, next);
+                    match chars.get(index)? {
+                        ',' => index += 1,
+                        '}' => return Some((JsonValue::Object(fields), index + 1)),
+                        _ => return None,
+                    }
+                }
+            }
+            '[' => {
+                let mut values = Vec::new();
+                let mut index = skip_whitespace(chars, index + 1);
+                if chars.get(index) == Some(&']') {
+                    return Some((JsonValue::Array(values), index + 1));
+                }
+                loop {
+                    let (value, next) = JsonValue::parse_value(chars, index, depth + 1)?;
+                    values.push(value);
+                    index = skip_whitespace(chars, next);
+                    match chars.get(index)? {
+                        ',' => index += 1,
+                        ']' => return Some((JsonValue::Array(values), index + 1)),
+                        _ => return None,
+                    }
+                }
+            }
+            '"' => parse_string(chars, index).map(|(value, next)| (JsonValue::String(value), next)),
+            _ => {
+                let mut end = index;
+                while end < chars.len() && !",]} \t\r\n".contains(chars[end]) {
+                    end += 1;
+                }
+                let word: String = chars[index..end].iter().collect();
+                let value = match word.as_str() {
+                    "null" | "true" | "false" => JsonValue::Literal,
+                    // Rust also reads "inf", "NaN" and numbers too large for an f64 as
+                    // infinity, none of which JSON allows.
+                    number => {
+                        JsonValue::Number(number.parse().ok().filter(|n: &f64| n.is_finite())?)
+                    }
+                };
+                Some((value, end))
+            }
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+fn skip_whitespace(chars: &[char], mut index: usize) -> usize {
+    while index < chars.len() && chars[index].is_ascii_whitespace() {
+        index += 1;
+    }
+    index
+}
+
+// Reads a quoted string starting at `index`, returning it and the index after the quote.
+fn parse_string(chars: &[char], index: usize) -> Option<(String, usize)> {
+    if chars.get(index) != Some(&'"') {
+        return None;
+    }
+    let mut value = String::new();
+    let mut index = index + 1;
+    loop {
+        match *chars.get(index)? {
+            '"' => return Some((value, index + 1)),
+            '\\' => {
+                let escaped = *chars.get(index + 1)?;
+                index += 2;
+                match escaped {
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    'b' => value.push('\x08'),
+                    'f' => value.push('\x0c'),
+                    'u' => {
+                        let mut code = hex_code(chars, index)?;
+                        index += 4;
+                        // Characters outside the BMP arrive as a surrogate pair.
+                        if (0xd800..0xdc00).contains(&code)
+                            && chars.get(index) == Some(&'\\')
+                            && chars.get(index + 1) == Some(&'u')
+                        {
+                            let low = hex_code(chars, index + 2)?;
+                            if (0xdc00..0xe000).contains(&low) {
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                                index += 6;
+                            }
+                        }
+                        value.push(std::char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    c => value.push(c),
+                }
+            }
+            c => {
+                value.push(c);
+                index += 1;
+            }
+        }
+    }
+}
+
+// from_str_radix alone would also accept a sign.
+fn hex_code(chars: &[char], index: usize) -> Option<u32> {
+    let digits = chars.get(index..index + 4)?;
+    if !digits.iter().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(&digits.iter().collect::<String>(), 16).ok()
+}
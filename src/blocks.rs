@@ -0,0 +1,125 @@
+use crate::{
+    background_parameter, foreground_parameter, BufferSizeError, Color, EscapeSequence,
+    SelectGraphicRenditionParameter,
+};
+use std::env;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColorDepth,
+    Color256Depth,
+    Color16Depth,
+}
+
+impl ColorDepth {
+    // Guesses what the terminal supports from COLORTERM and TERM, the same variables most
+    // terminal programs look at.
+    pub fn detect() -> ColorDepth {
+        let color_term = env::var("COLORTERM").unwrap_or_default();
+        if color_term == "truecolor" || color_term == "24bit" {
+            return ColorDepth::TrueColorDepth;
+        }
+        if env::var("TERM").unwrap_or_default().contains("256color") {
+            return ColorDepth::Color256Depth;
+        }
+        ColorDepth::Color16Depth
+    }
+
+    fn foreground_parameter(self, color: Color) -> SelectGraphicRenditionParameter {
+        foreground_parameter(self.degrade(color))
+    }
+
+    fn background_parameter(self, color: Color) -> SelectGraphicRenditionParameter {
+        background_parameter(self.degrade(color))
+    }
+
+    fn degrade(self, color: Color) -> Color {
+        match self {
+            ColorDepth::TrueColorDepth => color,
+            ColorDepth::Color256Depth => color.to_color256(),
+            ColorDepth::Color16Depth => Color::Color256(color.to_system_color()),
+        }
+    }
+}
+
+// Draws `width * height` RGB pixels (row order, three bytes each) two pixel rows per line of
+// text: the upper half block takes the top pixel as its foreground and the bottom pixel as its
+// background. Colors are only re-sent when they change, and every line ends with a reset.
+pub fn render_half_blocks(
+    width: usize,
+    height: usize,
+    rgb: &[u8],
+    color_depth: ColorDepth,
+) -> Result<String, BufferSizeError> {
+    BufferSizeError::check(rgb.len(), width, height, 3)?;
+    let pixel = |x: usize, y: usize| {
+        let offset = (y * width + x) * 3;
+        Color::RGB(
+            rgb[offset] as u32,
+            rgb[offset + 1] as u32,
+            rgb[offset + 2] as u32,
+        )
+    };
+    let mut lines = Vec::new();
+    for top in (0..height).step_by(2) {
+        let mut line = String::new();
+        let mut previous: Option<(Color, Option<Color>)> = None;
+        for x in 0..width {
+            let upper = pixel(x, top);
+            let lower = if top + 1 < height {
+                Some(pixel(x, top + 1))
+            } else {
+                None
+            };
+            if previous != Some((upper, lower)) {
+                let mut params = vec![color_depth.foreground_parameter(upper)];
+                params.push(match lower {
+                    Some(lower) => color_depth.background_parameter(lower),
+                    None => SelectGraphicRenditionParameter::DefaultBackgroundColorParameter,
+                });
+                line.push_str(&EscapeSequence::SelectGraphicRenditionSequence(params).to_string());
+                previous = Some((upper, lower));
+            }
+            line.push('▀');
+        }
+        line.push_str(
+            &EscapeSequence::SelectGraphicRenditionSequence(vec![
+                SelectGraphicRenditionParameter::ResetNormalParameter,
+            ])
+            .to_string(),
+        );
+        lines.push(line);
+    }
+    Ok(lines.join("\n"))
+}
+
+// Bits of a braille cell, indexed by [row][column] within its 2x4 dot grid.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+// Draws a `width * height` monochrome bitmap (row order, true for a set dot) with each braille
+// character covering two columns and four rows of dots.
+pub fn render_braille(
+    width: usize,
+    height: usize,
+    dots: &[bool],
+) -> Result<String, BufferSizeError> {
+    BufferSizeError::check(dots.len(), width, height, 1)?;
+    let mut lines = Vec::new();
+    for top in (0..height).step_by(4) {
+        let mut line = String::new();
+        for left in (0..width).step_by(2) {
+            let mut pattern = 0;
+            for (row, bits) in BRAILLE_DOTS.iter().enumerate() {
+                for (column, bit) in bits.iter().enumerate() {
+                    let (x, y) = (left + column, top + row);
+                    if x < width && y < height && dots[y * width + x] {
+                        pattern |= bit;
+                    }
+                }
+            }
+            line.push(std::char::from_u32(0x2800 + pattern).unwrap_or(' '));
+        }
+        lines.push(line);
+    }
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,141 @@
+use crate::{ASCIIControlCode, CharacterSet, EscapeSequence, GraphicSet};
+
+// The DEC Special Graphics set replaces 0x5F-0x7E; everything else is unchanged.
+const DEC_SPECIAL_GRAPHICS: [(char, char); 32] = [
+    ('_', '\u{a0}'),
+    ('`', '◆'),
+    ('a', '▒'),
+    ('b', '␉'),
+    ('c', '␌'),
+    ('d', '␍'),
+    ('e', '␊'),
+    ('f', '°'),
+    ('g', '±'),
+    ('h', '␤'),
+    ('i', '␋'),
+    ('j', '┘'),
+    ('k', '┐'),
+    ('l', '┌'),
+    ('m', '└'),
+    ('n', '┼'),
+    ('o', '⎺'),
+    ('p', '⎻'),
+    ('q', '─'),
+    ('r', '⎼'),
+    ('s', '⎽'),
+    ('t', '├'),
+    ('u', '┤'),
+    ('v', '┴'),
+    ('w', '┬'),
+    ('x', '│'),
+    ('y', '≤'),
+    ('z', '≥'),
+    ('{', 'π'),
+    ('|', '≠'),
+    ('}', '£'),
+    ('~', '·'),
+];
+
+// Heavy, double and rounded box drawing characters have no DEC equivalent and are drawn with
+// the nearest light line instead.
+const BOX_DRAWING_FALLBACKS: [(char, char); 40] = [
+    ('━', 'q'),
+    ('═', 'q'),
+    ('┃', 'x'),
+    ('║', 'x'),
+    ('┏', 'l'),
+    ('╔', 'l'),
+    ('╭', 'l'),
+    ('┓', 'k'),
+    ('╗', 'k'),
+    ('╮', 'k'),
+    ('┗', 'm'),
+    ('╚', 'm'),
+    ('╰', 'm'),
+    ('┛', 'j'),
+    ('╝', 'j'),
+    ('╯', 'j'),
+    ('┣', 't'),
+    ('╠', 't'),
+    ('╟', 't'),
+    ('╞', 't'),
+    ('┫', 'u'),
+    ('╣', 'u'),
+    ('╢', 'u'),
+    ('╡', 'u'),
+    ('┳', 'w'),
+    ('╦', 'w'),
+    ('╤', 'w'),
+    ('╥', 'w'),
+    ('┻', 'v'),
+    ('╩', 'v'),
+    ('╧', 'v'),
+    ('╨', 'v'),
+    ('╋', 'n'),
+    ('╬', 'n'),
+    ('╪', 'n'),
+    ('╫', 'n'),
+    ('╌', 'q'),
+    ('┄', 'q'),
+    ('╎', 'x'),
+    ('┆', 'x'),
+];
+
+pub fn dec_special_graphics_to_unicode(c: char) -> char {
+    DEC_SPECIAL_GRAPHICS
+        .iter()
+        .find(|(dec, _)| *dec == c)
+        .map(|(_, unicode)| *unicode)
+        .unwrap_or(c)
+}
+
+pub fn unicode_to_dec_special_graphics(c: char) -> Option<char> {
+    DEC_SPECIAL_GRAPHICS
+        .iter()
+        .find(|(_, unicode)| *unicode == c)
+        .map(|(dec, _)| *dec)
+        .or_else(|| {
+            BOX_DRAWING_FALLBACKS
+                .iter()
+                .find(|(unicode, _)| *unicode == c)
+                .map(|(_, dec)| *dec)
+        })
+}
+
+// Rewrites text so it can be shown on a terminal without Unicode support: G1 is designated as
+// DEC Special Graphics and every run of box drawing characters is shifted out to it.
+pub fn encode_dec_special_graphics(text: &str) -> String {
+    let mut encoded = EscapeSequence::DesignateCharacterSetSequence(
+        GraphicSet::G1GraphicSet,
+        CharacterSet::DECSpecialGraphicsCharacterSet,
+    )
+    .to_string();
+    let mut shifted_out = false;
+    for c in text.chars() {
+        let dec = if c.is_ascii() {
+            None
+        } else {
+            unicode_to_dec_special_graphics(c)
+        };
+        match dec {
+            Some(dec) => {
+                if !shifted_out {
+                    encoded.push_str(&ASCIIControlCode::ShiftOutCode.to_string());
+                    shifted_out = true;
+                }
+                encoded.push(dec);
+            }
+            None => {
+                if shifted_out {
+                    encoded.push_str(&ASCIIControlCode::ShiftInCode.to_string());
+                    shifted_out = false;
+                }
+                encoded.push(c);
+            }
+        }
+    }
+    if shifted_out {
+        encoded.push_str(&ASCIIControlCode::ShiftInCode.to_string());
+    }
+    encoded
+}
@@ -0,0 +1,38 @@
+use crate::{CursorStyle, EscapeSequence};
+use std::io::{self, Write};
+
+// Sets a cursor style for as long as it is alive and puts the terminal's default style back
+// when dropped, including while unwinding from a panic.
+pub struct CursorStyleGuard<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CursorStyleGuard<W> {
+    pub fn new(mut writer: W, cursor_style: CursorStyle) -> io::Result<CursorStyleGuard<W>> {
+        write_cursor_style(&mut writer, cursor_style)?;
+        Ok(CursorStyleGuard { writer })
+    }
+
+    pub fn set(&mut self, cursor_style: CursorStyle) -> io::Result<()> {
+        write_cursor_style(&mut self.writer, cursor_style)
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<W: Write> Drop for CursorStyleGuard<W> {
+    fn drop(&mut self) {
+        let _ = write_cursor_style(&mut self.writer, CursorStyle::DefaultCursorStyle);
+    }
+}
+
+fn write_cursor_style<W: Write>(writer: &mut W, cursor_style: CursorStyle) -> io::Result<()> {
+    writer.write_all(
+        EscapeSequence::SetCursorStyleSequence(cursor_style)
+            .to_string()
+            .as_bytes(),
+    )?;
+    writer.flush()
+}
@@ -0,0 +1,424 @@
+use crate::{parser, Color, OperatingSystemCommandParameter, Style, Theme, UnderlineStyle};
+
+// Links with other schemes, javascript: in particular, are dropped and only their text is kept.
+const LINK_SCHEMES: [&str; 5] = ["http", "https", "ftp", "mailto", "file"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HtmlMode {
+    InlineStyleMode,
+    ClassMode,
+}
+
+// Turns text containing SGR sequences and OSC 8 links into an HTML fragment meant to go
+// inside a <pre>. Other escape sequences and control codes are dropped.
+pub struct HtmlConverter {
+    mode: HtmlMode,
+    theme: Theme,
+    class_prefix: String,
+}
+
+impl HtmlConverter {
+    pub fn new() -> HtmlConverter {
+        HtmlConverter {
+            mode: HtmlMode::InlineStyleMode,
+            theme: Theme::xterm(),
+            class_prefix: String::from("ansi"),
+        }
+    }
+
+    // In ClassMode the colors come from HtmlConverter::stylesheet, except RGB and underline
+    // colors, which no class can cover and which are written inline.
+    pub fn with_mode(mut self, mode: HtmlMode) -> HtmlConverter {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> HtmlConverter {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_class_prefix(mut self, class_prefix: &str) -> HtmlConverter {
+        self.class_prefix = String::from(class_prefix);
+        self
+    }
+
+    pub fn convert(&self, input: &str) -> String {
+        let mut html = String::new();
+        let mut style = Style::new();
+        let mut open_span: Option<String> = None;
+        let mut open_link = false;
+        for segment in parser::parse(input) {
+            match segment {
+                parser::Segment::TextSegment(text) => {
+                    self.write_text(&mut html, &mut open_span, style, &text)
+                }
+                parser::Segment::ControlCodeSegment('\n') => {
+                    close_span(&mut html, &mut open_span);
+                    html.push('\n');
+                }
+                parser::Segment::ControlCodeSegment('\t') => {
+                    self.write_text(&mut html, &mut open_span, style, "\t")
+                }
+                parser::Segment::ControlSequenceSegment(sequence) => {
+                    let private = sequence
+                        .parameters
+                        .starts_with(|c| ('<'..='?').contains(&c));
+                    if sequence.final_byte == 'm' && sequence.intermediates.is_empty() && !private {
+                        style.apply(&sequence.parameters);
+                    }
+                }
+                parser::Segment::OperatingSystemCommandSegment(payload) => {
+                    match OperatingSystemCommandParameter::parse(&payload) {
+                        Some(OperatingSystemCommandParameter::HyperlinkStartParameter(
+                            hyperlink,
+                        )) => {
+                            close_span(&mut html, &mut open_span);
+                            close_link(&mut html, &mut open_link);
+                            if is_safe_link(&hyperlink.uri) {
+                                html.push_str(&format!("<a href=\"{}\">", escape(&hyperlink.uri)));
+                                open_link = true;
+                            }
+                        }
+                        Some(OperatingSystemCommandParameter::HyperlinkEndParameter) => {
+                            close_span(&mut html, &mut open_span);
+                            close_link(&mut html, &mut open_link);
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        close_span(&mut html, &mut open_span);
+        close_link(&mut html, &mut open_link);
+        html
+    }
+
+    // Rules for ClassMode. The element wrapping the fragment takes the bare prefix as its
+    // class to get the theme's default colors.
+    pub fn stylesheet(&self) -> String {
+        let prefix = &self.class_prefix;
+        let mut css = format!(
+            ".{} {{ color: {}; background-color: {}; }}\n",
+            prefix,
+            hex(self.theme.resolve(self.theme.foreground)),
+            hex(self.theme.resolve(self.theme.background))
+        );
+        let blink = format!("animation: {}-blink 1s steps(1) infinite", prefix);
+        let rules = [
+            ("bold", "font-weight: bold"),
+            ("faint", "opacity: 0.5"),
+            ("italic", "font-style: italic"),
+            ("conceal", "visibility: hidden"),
+            ("blink", blink.as_str()),
+            ("underline", "text-decoration-line: underline"),
+            ("strike", "text-decoration-line: line-through"),
+            ("overline", "text-decoration-line: overline"),
+            (
+                "underline.PREFIX-strike",
+                "text-decoration-line: underline line-through",
+            ),
+            (
+                "underline.PREFIX-overline",
+                "text-decoration-line: underline overline",
+            ),
+            (
+                "strike.PREFIX-overline",
+                "text-decoration-line: line-through overline",
+            ),
+            (
+                "underline.PREFIX-strike.PREFIX-overline",
+                "text-decoration-line: underline line-through overline",
+            ),
+            ("underline-double", "text-decoration-style: double"),
+            ("underline-curly", "text-decoration-style: wavy"),
+            ("underline-dotted", "text-decoration-style: dotted"),
+            ("underline-dashed", "text-decoration-style: dashed"),
+        ];
+        for (class, declaration) in rules.iter() {
+            css.push_str(&format!(
+                ".{}-{} {{ {}; }}\n",
+                prefix,
+                class.replace("PREFIX", prefix),
+                declaration
+            ));
+        }
+        css.push_str(&format!(
+            "@keyframes {}-blink {{ 50% {{ opacity: 0; }} }}\n",
+            prefix
+        ));
+        css.push_str(&format!(
+            ".{}-fg-inverse {{ color: {}; }}\n.{}-bg-inverse {{ background-color: {}; }}\n",
+            prefix,
+            hex(self.theme.resolve(self.theme.background)),
+            prefix,
+            hex(self.theme.resolve(self.theme.foreground))
+        ));
+        for color_code in 0..256 {
+            let color = hex(self.theme.resolve(Color::Color256(color_code)));
+            css.push_str(&format!(
+                ".{}-fg-{} {{ color: {}; }}\n.{}-bg-{} {{ background-color: {}; }}\n",
+                prefix, color_code, color, prefix, color_code, color
+            ));
+        }
+        css
+    }
+
+    fn write_text(
+        &self,
+        html: &mut String,
+        open_span: &mut Option<String>,
+        style: Style,
+        text: &str,
+    ) {
+        if text.is_empty() {
+            return;
+        }
+        let attributes = self.attributes(style);
+        let attributes = if attributes.is_empty() {
+            None
+        } else {
+            Some(attributes)
+        };
+        if *open_span != attributes {
+            close_span(html, open_span);
+            if let Some(attributes) = &attributes {
+                html.push_str(&format!("<span {}>", attributes));
+            }
+            *open_span = attributes;
+        }
+        html.push_str(&escape(text));
+    }
+
+    fn attributes(&self, style: Style) -> String {
+        // Reverse video swaps the colors, falling back to the theme's defaults when unset.
+        let (foreground, background) = if style.reverse {
+            (
+                Some(
+                    style
+                        .background
+                        .map_or(Paint::ThemeBackground, Paint::Color),
+                ),
+                Some(
+                    style
+                        .foreground
+                        .map_or(Paint::ThemeForeground, Paint::Color),
+                ),
+            )
+        } else {
+            (
+                style.foreground.map(Paint::Color),
+                style.background.map(Paint::Color),
+            )
+        };
+        match self.mode {
+            HtmlMode::InlineStyleMode => self.inline_attributes(style, foreground, background),
+            HtmlMode::ClassMode => self.class_attributes(style, foreground, background),
+        }
+    }
+
+    fn inline_attributes(
+        &self,
+        style: Style,
+        foreground: Option<Paint>,
+        background: Option<Paint>,
+    ) -> String {
+        let mut declarations = Vec::new();
+        if let Some(foreground) = foreground {
+            declarations.push(format!("color:{}", self.paint(foreground)));
+        }
+        if let Some(background) = background {
+            declarations.push(format!("background-color:{}", self.paint(background)));
+        }
+        if style.bold {
+            declarations.push(String::from("font-weight:bold"));
+        }
+        if style.faint {
+            declarations.push(String::from("opacity:0.5"));
+        }
+        if style.italic {
+            declarations.push(String::from("font-style:italic"));
+        }
+        if style.conceal {
+            declarations.push(String::from("visibility:hidden"));
+        }
+        let mut lines = Vec::new();
+        if style.underline != UnderlineStyle::NoUnderline {
+            lines.push("underline");
+        }
+        if style.crossed_out {
+            lines.push("line-through");
+        }
+        if style.overlined {
+            lines.push("overline");
+        }
+        // Inline styles cannot refer to the keyframes ClassMode uses, so blinking falls back to
+        // the text decoration, which few browsers still render.
+        if style.blink {
+            lines.push("blink");
+        }
+        if !lines.is_empty() {
+            declarations.push(format!("text-decoration-line:{}", lines.join(" ")));
+        }
+        if let Some(decoration_style) = decoration_style(style.underline) {
+            declarations.push(format!("text-decoration-style:{}", decoration_style));
+        }
+        if let Some(color) = style.underline_color {
+            declarations.push(format!(
+                "text-decoration-color:{}",
+                hex(self.theme.resolve(color))
+            ));
+        }
+        if declarations.is_empty() {
+            return String::new();
+        }
+        format!("style=\"{}\"", declarations.join(";"))
+    }
+
+    fn class_attributes(
+        &self,
+        style: Style,
+        foreground: Option<Paint>,
+        background: Option<Paint>,
+    ) -> String {
+        let prefix = &self.class_prefix;
+        let mut classes = Vec::new();
+        let mut declarations = Vec::new();
+        match foreground {
+            Some(Paint::Color(Color::Color256(color_code))) => {
+                classes.push(format!("{}-fg-{}", prefix, color_code.min(255)))
+            }
+            Some(Paint::Color(color)) => {
+                declarations.push(format!("color:{}", hex(self.theme.resolve(color))))
+            }
+            Some(_) => classes.push(format!("{}-fg-inverse", prefix)),
+            None => {}
+        }
+        match background {
+            Some(Paint::Color(Color::Color256(color_code))) => {
+                classes.push(format!("{}-bg-{}", prefix, color_code.min(255)))
+            }
+            Some(Paint::Color(color)) => declarations.push(format!(
+                "background-color:{}",
+                hex(self.theme.resolve(color))
+            )),
+            Some(_) => classes.push(format!("{}-bg-inverse", prefix)),
+            None => {}
+        }
+        let flags = [
+            (style.bold, "bold"),
+            (style.faint, "faint"),
+            (style.italic, "italic"),
+            (style.conceal, "conceal"),
+            (style.blink, "blink"),
+            (style.underline != UnderlineStyle::NoUnderline, "underline"),
+            (style.crossed_out, "strike"),
+            (style.overlined, "overline"),
+        ];
+        for (enabled, class) in flags.iter() {
+            if *enabled {
+                classes.push(format!("{}-{}", prefix, class));
+            }
+        }
+        if let Some(decoration_style) = decoration_style(style.underline) {
+            let class = if decoration_style == "wavy" {
+                "curly"
+            } else {
+                decoration_style
+            };
+            classes.push(format!("{}-underline-{}", prefix, class));
+        }
+        if let Some(color) = style.underline_color {
+            declarations.push(format!(
+                "text-decoration-color:{}",
+                hex(self.theme.resolve(color))
+            ));
+        }
+        let mut attributes = Vec::new();
+        if !classes.is_empty() {
+            attributes.push(format!("class=\"{}\"", escape(&classes.join(" "))));
+        }
+        if !declarations.is_empty() {
+            attributes.push(format!("style=\"{}\"", declarations.join(";")));
+        }
+        attributes.join(" ")
+    }
+
+    fn paint(&self, paint: Paint) -> String {
+        match paint {
+            Paint::Color(color) => hex(self.theme.resolve(color)),
+            Paint::ThemeBackground => hex(self.theme.resolve(self.theme.background)),
+            Paint::ThemeForeground => hex(self.theme.resolve(self.theme.foreground)),
+        }
+    }
+}
+
+impl Default for HtmlConverter {
+    fn default() -> HtmlConverter {
+        HtmlConverter::new()
+    }
+}
+
+// What a span is painted with once reverse video has been applied, which can turn the theme's
+// default background into the text color and its default foreground into the background.
+#[derive(Clone, Copy)]
+enum Paint {
+    Color(Color),
+    ThemeBackground,
+    ThemeForeground,
+}
+
+fn decoration_style(underline: UnderlineStyle) -> Option<&'static str> {
+    match underline {
+        UnderlineStyle::DoubleUnderline => Some("double"),
+        UnderlineStyle::CurlyUnderline => Some("wavy"),
+        UnderlineStyle::DottedUnderline => Some("dotted"),
+        UnderlineStyle::DashedUnderline => Some("dashed"),
+        _ => None,
+    }
+}
+
+fn close_span(html: &mut String, open_span: &mut Option<String>) {
+    if open_span.take().is_some() {
+        html.push_str("</span>");
+    }
+}
+
+fn close_link(html: &mut String, open_link: &mut bool) {
+    if *open_link {
+        html.push_str("</a>");
+        *open_link = false;
+    }
+}
+
+fn is_safe_link(uri: &str) -> bool {
+    match uri.split_once(':') {
+        Some((scheme, _)) => LINK_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()),
+        None => false,
+    }
+}
+
+pub(crate) fn hex((red, green, blue): (u32, u32, u32)) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        red.min(255),
+        green.min(255),
+        blue.min(255)
+    )
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,317 @@
+use crate::{base64, parser, ApplicationProgramCommandPart, BufferSizeError};
+use std::fmt;
+
+// The protocol limits each escape sequence to 4096 bytes of base64 payload.
+const CHUNK_SIZE: usize = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KittyImageFormat {
+    RgbFormat,
+    RgbaFormat,
+    PngFormat,
+}
+
+impl fmt::Display for KittyImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            KittyImageFormat::RgbFormat => String::from("24"),
+            KittyImageFormat::RgbaFormat => String::from("32"),
+            KittyImageFormat::PngFormat => String::from("100"),
+        };
+        f.write_str(&text)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KittyImage {
+    pub format: KittyImageFormat,
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub image_id: Option<u32>,
+}
+
+impl KittyImage {
+    // The terminal reads the dimensions from the PNG itself.
+    pub fn png(data: Vec<u8>) -> KittyImage {
+        KittyImage {
+            format: KittyImageFormat::PngFormat,
+            data,
+            width: 0,
+            height: 0,
+            image_id: None,
+        }
+    }
+
+    // Pixels in row order. Bytes past the last pixel are dropped, since the terminal expects the
+    // exact size.
+    pub fn rgb(width: u32, height: u32, mut data: Vec<u8>) -> Result<KittyImage, BufferSizeError> {
+        let (columns, rows) = (width as usize, height as usize);
+        BufferSizeError::check(data.len(), columns, rows, 3)?;
+        data.truncate(columns * rows * 3);
+        Ok(KittyImage {
+            format: KittyImageFormat::RgbFormat,
+            data,
+            width,
+            height,
+            image_id: None,
+        })
+    }
+
+    pub fn rgba(width: u32, height: u32, mut data: Vec<u8>) -> Result<KittyImage, BufferSizeError> {
+        let (columns, rows) = (width as usize, height as usize);
+        BufferSizeError::check(data.len(), columns, rows, 4)?;
+        data.truncate(columns * rows * 4);
+        Ok(KittyImage {
+            format: KittyImageFormat::RgbaFormat,
+            data,
+            width,
+            height,
+            image_id: None,
+        })
+    }
+
+    // An id lets the image be placed again or deleted later without resending it.
+    pub fn with_id(mut self, image_id: u32) -> KittyImage {
+        self.image_id = Some(image_id);
+        self
+    }
+
+    fn keys(&self) -> Vec<(char, String)> {
+        let mut keys = vec![('f', self.format.to_string())];
+        if self.format != KittyImageFormat::PngFormat {
+            keys.push(('s', self.width.to_string()));
+            keys.push(('v', self.height.to_string()));
+        }
+        if let Some(image_id) = self.image_id {
+            keys.push(('i', image_id.to_string()));
+        }
+        keys
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KittyPlacement {
+    pub placement_id: Option<u32>,
+    pub columns: Option<u32>,
+    pub rows: Option<u32>,
+    pub x_offset: Option<u32>,
+    pub y_offset: Option<u32>,
+    pub z_index: Option<i32>,
+}
+
+impl KittyPlacement {
+    pub fn new() -> KittyPlacement {
+        KittyPlacement::default()
+    }
+
+    pub fn with_id(mut self, placement_id: u32) -> KittyPlacement {
+        self.placement_id = Some(placement_id);
+        self
+    }
+
+    // Scales the image to fill this many cells.
+    pub fn with_size(mut self, columns: u32, rows: u32) -> KittyPlacement {
+        self.columns = Some(columns);
+        self.rows = Some(rows);
+        self
+    }
+
+    // Pixel offset of the image inside the cell at the cursor.
+    pub fn with_offset(mut self, x_offset: u32, y_offset: u32) -> KittyPlacement {
+        self.x_offset = Some(x_offset);
+        self.y_offset = Some(y_offset);
+        self
+    }
+
+    // Negative values draw the image below the text.
+    pub fn with_z_index(mut self, z_index: i32) -> KittyPlacement {
+        self.z_index = Some(z_index);
+        self
+    }
+
+    fn keys(&self) -> Vec<(char, String)> {
+        let values = [
+            ('p', self.placement_id.map(|value| value.to_string())),
+            ('c', self.columns.map(|value| value.to_string())),
+            ('r', self.rows.map(|value| value.to_string())),
+            ('X', self.x_offset.map(|value| value.to_string())),
+            ('Y', self.y_offset.map(|value| value.to_string())),
+            ('z', self.z_index.map(|value| value.to_string())),
+        ];
+        values
+            .iter()
+            .filter_map(|(key, value)| value.clone().map(|value| (*key, value)))
+            .collect()
+    }
+}
+
+// Lower case deletions only remove placements; upper case ones also free the stored image data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KittyDeletion {
+    DeleteAllDeletion,
+    DeleteImageDeletion(u32),
+    DeletePlacementDeletion(u32, u32), // image id, placement id
+    DeleteAtCursorDeletion,
+    DeleteZIndexDeletion(i32),
+}
+
+impl KittyDeletion {
+    fn keys(self, free_data: bool) -> Vec<(char, String)> {
+        let target = |target: char| {
+            if free_data {
+                target.to_ascii_uppercase().to_string()
+            } else {
+                target.to_string()
+            }
+        };
+        match self {
+            KittyDeletion::DeleteAllDeletion => vec![('d', target('a'))],
+            KittyDeletion::DeleteImageDeletion(image_id) => {
+                vec![('d', target('i')), ('i', image_id.to_string())]
+            }
+            KittyDeletion::DeletePlacementDeletion(image_id, placement_id) => vec![
+                ('d', target('i')),
+                ('i', image_id.to_string()),
+                ('p', placement_id.to_string()),
+            ],
+            KittyDeletion::DeleteAtCursorDeletion => vec![('d', target('c'))],
+            KittyDeletion::DeleteZIndexDeletion(z_index) => {
+                vec![('d', target('z')), ('z', z_index.to_string())]
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KittyGraphicsCommand {
+    TransmitCommand(KittyImage),
+    TransmitAndDisplayCommand(KittyImage, KittyPlacement),
+    DisplayCommand(u32, KittyPlacement), // image id
+    DeleteCommand(KittyDeletion, bool),  // free the image data as well
+}
+
+impl fmt::Display for KittyGraphicsCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            KittyGraphicsCommand::TransmitCommand(image) => {
+                let mut keys = vec![('a', String::from("t"))];
+                keys.extend(image.keys());
+                transmission(keys, &image.data)
+            }
+            KittyGraphicsCommand::TransmitAndDisplayCommand(image, placement) => {
+                let mut keys = vec![('a', String::from("T"))];
+                keys.extend(image.keys());
+                keys.extend(placement.keys());
+                transmission(keys, &image.data)
+            }
+            KittyGraphicsCommand::DisplayCommand(image_id, placement) => {
+                let mut keys = vec![('a', String::from("p")), ('i', image_id.to_string())];
+                keys.extend(placement.keys());
+                command(&keys, "")
+            }
+            KittyGraphicsCommand::DeleteCommand(deletion, free_data) => {
+                let mut keys = vec![('a', String::from("d"))];
+                keys.extend(deletion.keys(*free_data));
+                command(&keys, "")
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+fn command(keys: &[(char, String)], payload: &str) -> String {
+    let keys: Vec<String> = keys
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    let payload = if payload.is_empty() {
+        String::new()
+    } else {
+        format!(";{}", payload)
+    };
+    format!(
+        "{}G{}{}{}",
+        ApplicationProgramCommandPart::ApplicationProgramCommandIntroducer,
+        keys.join(","),
+        payload,
+        ApplicationProgramCommandPart::StringTerminator
+    )
+}
+
+// Only the first chunk carries the control keys; every chunk says whether more follow.
+fn transmission(mut keys: Vec<(char, String)>, data: &[u8]) -> String {
+    let encoded = base64::encode(data);
+    let chunks: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(CHUNK_SIZE)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or(""))
+        .collect();
+    if chunks.len() <= 1 {
+        return command(&keys, &encoded);
+    }
+    let mut sequence = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = if index + 1 < chunks.len() { "1" } else { "0" };
+        if index == 0 {
+            keys.push(('m', String::from(more)));
+            sequence.push_str(&command(&keys, chunk));
+        } else {
+            sequence.push_str(&command(&[('m', String::from(more))], chunk));
+        }
+    }
+    sequence
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KittyGraphicsStatus {
+    OkStatus,
+    ErrorStatus(String, String), // code such as ENOENT, message
+}
+
+// A reply such as "\x1b_Gi=31;OK\x1b\\" or "\x1b_Gi=31;ENOENT:No such image\x1b\\".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KittyGraphicsResponse {
+    pub image_id: Option<u32>,
+    pub image_number: Option<u32>,
+    pub placement_id: Option<u32>,
+    pub status: KittyGraphicsStatus,
+}
+
+impl KittyGraphicsResponse {
+    pub fn parse(sequence: &str) -> Option<KittyGraphicsResponse> {
+        parser::parse(sequence)
+            .into_iter()
+            .find_map(|segment| match segment {
+                parser::Segment::ApplicationProgramCommandSegment(payload) => {
+                    KittyGraphicsResponse::parse_payload(&payload)
+                }
+                _ => None,
+            })
+    }
+
+    fn parse_payload(payload: &str) -> Option<KittyGraphicsResponse> {
+        let (keys, message) = payload.strip_prefix('G')?.split_once(';')?;
+        let mut response = KittyGraphicsResponse {
+            image_id: None,
+            image_number: None,
+            placement_id: None,
+            status: KittyGraphicsStatus::OkStatus,
+        };
+        for key in keys.split(',').filter(|key| !key.is_empty()) {
+            let (key, value) = key.split_once('=')?;
+            match key {
+                "i" => response.image_id = value.parse().ok(),
+                "I" => response.image_number = value.parse().ok(),
+                "p" => response.placement_id = value.parse().ok(),
+                _ => {}
+            }
+        }
+        if message != "OK" {
+            let (code, description) = message.split_once(':').unwrap_or((message, ""));
+            response.status =
+                KittyGraphicsStatus::ErrorStatus(String::from(code), String::from(description));
+        }
+        Some(response)
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{parser, width, EscapeSequence, OperatingSystemCommandParameter, Style};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    LeftAlignment,
+    RightAlignment,
+    CenterAlignment, // any odd column of padding goes on the right
+}
+
+// Cuts text down to `width` columns, ending it with the ellipsis in whatever style was active
+// at the cut. Text that already fits is returned unchanged.
+pub fn truncate_to_width(text: &str, width: usize, ellipsis: &str) -> String {
+    if width::display_width(text) <= width {
+        return String::from(text);
+    }
+    let ellipsis_width = width::display_width(ellipsis);
+    if ellipsis_width > width {
+        return slice_columns(text, 0, width);
+    }
+    let mut cut = Cut::new(text, 0, width - ellipsis_width);
+    cut.output.push_str(ellipsis);
+    cut.close()
+}
+
+// Pads text with unstyled spaces up to `width` columns. Text that is already as wide or wider
+// is left as it is, apart from closing any style or link it leaves open.
+pub fn pad_to_width(text: &str, width: usize, alignment: Alignment) -> String {
+    let padding = width.saturating_sub(width::display_width(text));
+    let (left, right) = match alignment {
+        Alignment::LeftAlignment => (0, padding),
+        Alignment::RightAlignment => (padding, 0),
+        Alignment::CenterAlignment => (padding / 2, padding - padding / 2),
+    };
+    let mut cut = Cut::new(text, 0, usize::MAX);
+    cut.output.insert_str(0, &" ".repeat(left));
+    let mut padded = cut.close();
+    padded.push_str(&" ".repeat(right));
+    padded
+}
+
+// The columns from `start` up to but not including `end`. The slice opens with the style and
+// link active at `start` and closes whatever is still active at `end`. Halves of wide
+// characters cut by either edge become spaces, so the slice is always end - start columns
+// wide when the text is long enough.
+pub fn slice_columns(text: &str, start: usize, end: usize) -> String {
+    Cut::new(text, start, end).close()
+}
+
+// The graphic rendition and hyperlink state that styled text leaves active, which has to be
+// re-established after a cut and closed before one.
+#[derive(Clone, Default)]
+pub(crate) struct RenditionState {
+    style: Style,
+    link: Option<String>, // payload of the OSC 8 that opened the active link
+}
+
+impl RenditionState {
+    pub(crate) fn apply(&mut self, segment: &parser::Segment) {
+        match segment {
+            parser::Segment::ControlSequenceSegment(sequence)
+                if sequence.final_byte == 'm' && sequence.intermediates.is_empty() =>
+            {
+                self.style.apply(&sequence.parameters)
+            }
+            parser::Segment::OperatingSystemCommandSegment(payload) => {
+                match OperatingSystemCommandParameter::parse(payload) {
+                    Some(OperatingSystemCommandParameter::HyperlinkStartParameter(_)) => {
+                        self.link = Some(payload.clone())
+                    }
+                    Some(OperatingSystemCommandParameter::HyperlinkEndParameter) => {
+                        self.link = None
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn opening(&self) -> String {
+        let mut opening = String::new();
+        if !self.style.is_default() {
+            opening.push_str(&self.style.to_string());
+        }
+        if let Some(payload) = &self.link {
+            opening.push_str(
+                &parser::Segment::OperatingSystemCommandSegment(payload.clone()).to_string(),
+            );
+        }
+        opening
+    }
+
+    pub(crate) fn closing(&self) -> String {
+        let mut closing = String::new();
+        if !self.style.is_default() {
+            closing.push_str(&Style::new().to_string());
+        }
+        if self.link.is_some() {
+            closing.push_str(
+                &EscapeSequence::OperatingSystemCommandSequence(
+                    OperatingSystemCommandParameter::HyperlinkEndParameter,
+                )
+                .to_string(),
+            );
+        }
+        closing
+    }
+}
+
+// The kept part of a text along with the state active at its end.
+struct Cut {
+    output: String,
+    state: RenditionState,
+}
+
+impl Cut {
+    fn new(text: &str, start: usize, end: usize) -> Cut {
+        let mut cut = Cut {
+            output: String::new(),
+            state: RenditionState::default(),
+        };
+        let mut column = 0;
+        // Sequences before the first kept column are only tracked, then replayed as one SGR
+        // and link once text is kept. From column 0 they are copied as they are.
+        let mut started = start == 0;
+        for segment in parser::parse(text) {
+            match segment {
+                parser::Segment::TextSegment(text) => {
+                    for (cluster, cluster_width) in width::clusters(&text) {
+                        let next = column + cluster_width;
+                        // Zero width clusters at the start column belong to the slice.
+                        let before_start =
+                            next <= start && !(cluster_width == 0 && column >= start);
+                        if before_start || column >= end {
+                            column = next;
+                            continue;
+                        }
+                        if !started {
+                            cut.output.push_str(&cut.state.opening());
+                            started = true;
+                        }
+                        if column >= start && next <= end {
+                            cut.output.push_str(&cluster);
+                        } else {
+                            let visible = next.min(end) - column.max(start);
+                            cut.output.push_str(&" ".repeat(visible));
+                        }
+                        column = next;
+                    }
+                }
+                // What comes after the last kept column has no effect on the cut.
+                _ if column >= end => {}
+                segment => {
+                    cut.state.apply(&segment);
+                    if started {
+                        cut.output.push_str(&segment.to_string());
+                    }
+                }
+            }
+        }
+        cut
+    }
+
+    fn close(mut self) -> String {
+        self.output.push_str(&self.state.closing());
+        self.output
+    }
+}
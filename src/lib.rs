@@ -1,4 +1,78 @@
 use std::fmt;
+pub mod asciicast;
+mod base64;
+pub mod blocks;
+pub mod charset;
+pub mod cursor;
+pub mod html;
+pub mod kitty;
+pub mod layout;
+pub mod osc;
+pub mod parser;
+pub mod render;
+pub mod sanitize;
+pub mod screen;
+pub mod sixel;
+pub mod style;
+pub mod styled;
+pub mod svg;
+pub mod tabs;
+// Terminal control is written against the termios ABI of the platforms listed here; see
+// terminal.rs.
+#[cfg(all(
+    target_os = "linux",
+    any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv64"
+    )
+))]
+pub mod terminal;
+pub mod theme;
+pub mod width;
+pub mod wrap;
+
+pub use asciicast::{AsciicastEvent, AsciicastEventCode, AsciicastHeader, Player, Recorder};
+pub use blocks::{render_braille, render_half_blocks, ColorDepth};
+pub use charset::{
+    dec_special_graphics_to_unicode, encode_dec_special_graphics, unicode_to_dec_special_graphics,
+};
+pub use cursor::CursorStyleGuard;
+pub use html::{HtmlConverter, HtmlMode};
+pub use kitty::{
+    KittyDeletion, KittyGraphicsCommand, KittyGraphicsResponse, KittyGraphicsStatus, KittyImage,
+    KittyImageFormat, KittyPlacement,
+};
+pub use layout::{pad_to_width, slice_columns, truncate_to_width, Alignment};
+pub use osc::{
+    parse_color_spec, Hyperlink, ImageDimension, InlineImage, LinkedText,
+    OperatingSystemCommandParameter, SelectionTarget,
+};
+pub use parser::{ControlSequence, Segment};
+pub use render::DiffRenderer;
+pub use sanitize::{ReplacementNotation, SanitizePolicy, SequenceCategory};
+pub use screen::{Cell, Screen};
+pub use sixel::SixelEncoder;
+pub use style::{Style, UnderlineStyle};
+pub use styled::StyledString;
+pub use svg::SvgRenderer;
+pub use tabs::set_tab_stops;
+#[cfg(all(
+    target_os = "linux",
+    any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv64"
+    )
+))]
+pub use terminal::*;
+pub use theme::Theme;
+pub use width::{char_width, display_width, text_width};
+pub use wrap::{wrap_text, TextWrapper};
 
 pub enum ASCIIControlCode {
     NullCode,
@@ -37,14 +111,45 @@ pub enum ASCIIControlCode {
     DeleteCode,
 }
 
-impl ASCIIControlCode {
-    pub fn to_string(self) -> String {
-        match self {
-            ASCIIControlCode::EscapeCode => String::from("\x1b"),
+impl fmt::Display for ASCIIControlCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            ASCIIControlCode::NullCode => String::from("\x00"),
+            ASCIIControlCode::StartOfHeadingCode => String::from("\x01"),
+            ASCIIControlCode::StartOfTextCode => String::from("\x02"),
+            ASCIIControlCode::EndofTextCode => String::from("\x03"),
+            ASCIIControlCode::EndOfTransmissionCode => String::from("\x04"),
+            ASCIIControlCode::EnquiryCode => String::from("\x05"),
+            ASCIIControlCode::AcknowledgeCode => String::from("\x06"),
+            ASCIIControlCode::BellCode => String::from("\x07"),
+            ASCIIControlCode::BackspaceCode => String::from("\x08"),
+            ASCIIControlCode::HorizontalTabCode => String::from("\x09"),
             ASCIIControlCode::LineFeedCode=> String::from("\x0A"),
+            ASCIIControlCode::VerticalTabCode => String::from("\x0B"),
+            ASCIIControlCode::FormFeedCode => String::from("\x0C"),
             ASCIIControlCode::CarriageReturnCode => String::from("\x0D"),
-            _ => panic!(), // TODO: Implement the rest
-        }
+            ASCIIControlCode::ShiftOutCode => String::from("\x0E"),
+            ASCIIControlCode::ShiftInCode => String::from("\x0F"),
+            ASCIIControlCode::DataLinkEscapeCode => String::from("\x10"),
+            ASCIIControlCode::DeviceControlOneCode => String::from("\x11"),
+            ASCIIControlCode::DeviceControlTwoCode => String::from("\x12"),
+            ASCIIControlCode::DeviceControlThreeCode => String::from("\x13"),
+            ASCIIControlCode::DeviceControlFourCode => String::from("\x14"),
+            ASCIIControlCode::NegativeAcknowledgeCode => String::from("\x15"),
+            ASCIIControlCode::SynchronousIdleCode => String::from("\x16"),
+            ASCIIControlCode::EndOfTransmissionBlockCode => String::from("\x17"),
+            ASCIIControlCode::CancelCode => String::from("\x18"),
+            ASCIIControlCode::EndOfMediumCode => String::from("\x19"),
+            ASCIIControlCode::SubsituteCode => String::from("\x1A"),
+            ASCIIControlCode::EscapeCode => String::from("\x1b"),
+            ASCIIControlCode::FileSeperatorCode => String::from("\x1C"),
+            ASCIIControlCode::GroupSeperatorCode => String::from("\x1D"),
+            ASCIIControlCode::RecordSeperatorCode => String::from("\x1E"),
+            ASCIIControlCode::UnitSeperatorCode => String::from("\x1F"),
+            ASCIIControlCode::SpaceCode => String::from(" "),
+            ASCIIControlCode::DeleteCode => String::from("\x7F"),
+        };
+        f.write_str(&text)
     }
 }
 
@@ -73,83 +178,229 @@ pub enum ControlSequenceInducerParameter {
     DeviceStatusReportParameter,
     SaveCursorPositionParameter,
     RestoreCursorPositionParameter,
+    PushTitleParameter,
+    PopTitleParameter,
+    SetCursorStyleParameter(CursorStyle),
+    ShowCursorParameter,
+    HideCursorParameter,
+    SetTopBottomMarginsParameter(u32, u32),
+    ResetTopBottomMarginsParameter,
+    EnableLeftRightMarginModeParameter,
+    DisableLeftRightMarginModeParameter,
+    BeginSynchronizedUpdateParameter, // terminals hold back drawing until the update ends
+    EndSynchronizedUpdateParameter,
+    SetLeftRightMarginsParameter(u32, u32), // only honoured while left/right margin mode is on
+    InsertLineParameter(u32),
+    DeleteLineParameter(u32),
+    InsertCharacterParameter(u32),
+    DeleteCharacterParameter(u32),
+    EraseCharacterParameter(u32),
+    RepeatParameter(u32), // repeats the preceding graphic character
+    ClearTabStopParameter,
+    ClearAllTabStopsParameter,
+    CursorForwardTabulationParameter(u32),
+    CursorBackwardTabulationParameter(u32),
+    CharacterPositionAbsoluteParameter(u32),
+    CharacterPositionForwardParameter(u32),
+    CharacterPositionBackwardParameter(u32),
+    VerticalPositionAbsoluteParameter(u32),
+    VerticalPositionForwardParameter(u32),
+    VerticalPositionBackwardParameter(u32),
+    CursorLineTabulationParameter(u32),
 }
 
-impl ControlSequenceInducerParameter {
-    pub fn to_string(self) -> String {
-        match self {
+impl fmt::Display for ControlSequenceInducerParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
             ControlSequenceInducerParameter::CursorUpParameter(move_count) => {
-                return format!("{}A", move_count.to_string());
+                format!("{}A", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::CursorDownParameter(move_count) => {
-                return format!("{}B", move_count.to_string());
+                format!("{}B", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::CursorForwardParameter(move_count) => {
-                return format!("{}C", move_count.to_string());
+                format!("{}C", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::CursorBackParameter(move_count) => {
-                return format!("{}D", move_count.to_string());
+                format!("{}D", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::CursorNextLineParameter(move_count) => {
-                return format!("{}E", move_count.to_string());
+                format!("{}E", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::CursorPreviousLineParameter(move_count) => {
-                return format!("{}F", move_count.to_string());
+                format!("{}F", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::CursorHorizontalAbsoluteParameter(move_count) => {
-                return format!("{}F", move_count.to_string());
+                format!("{}G", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::CursorPositionParameter(
                 row_position,
                 column_position,
-            ) => {
-                return format!(
-                    "{};{}H",
-                    row_position.to_string(),
-                    column_position.to_string()
-                );
-            }
-            ControlSequenceInducerParameter::EraseScreenAfterCursorParameter => String::from("0J"),
+            ) => position_parameters(*row_position, *column_position, "H"),
+            ControlSequenceInducerParameter::EraseScreenAfterCursorParameter => String::from("J"),
             ControlSequenceInducerParameter::EraseScreenBeforeCursorParameter => String::from("1J"),
             ControlSequenceInducerParameter::EraseScreenParameter => String::from("2J"),
             ControlSequenceInducerParameter::EraseScreenAndScrollbackParameter => {
                 String::from("3J")
             }
-            ControlSequenceInducerParameter::EraseLineAfterCursorParameter => String::from("0K"),
+            ControlSequenceInducerParameter::EraseLineAfterCursorParameter => String::from("K"),
             ControlSequenceInducerParameter::EraseLineBeforeCursorParameter => String::from("1K"),
             ControlSequenceInducerParameter::EraseLineParameter => String::from("2K"),
             ControlSequenceInducerParameter::ScrollUpParameter(move_count) => {
-                return format!("{}S", move_count.to_string());
+                format!("{}S", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::ScrollDownParameter(move_count) => {
-                return format!("{}T", move_count.to_string());
+                format!("{}T", optional_parameter(*move_count))
             }
             ControlSequenceInducerParameter::HorizontalVerticalPositionParameter(
                 row_position,
                 column_position,
-            ) => {
-                return format!(
-                    "{};{}f",
-                    row_position.to_string(),
-                    column_position.to_string()
-                );
-            }
+            ) => position_parameters(*row_position, *column_position, "f"),
             ControlSequenceInducerParameter::SelectGraphicRenditionSequenceParameter(params) => {
-                let s: String = params
-                    .into_iter()
-                    .map(|param| format!("{};", param.to_string()))
-                    .collect();
-                return format!("{}m", s);
+                let s: Vec<String> = params.iter().map(|param| param.to_string()).collect();
+                format!("{}m", s.join(";"))
             }
             ControlSequenceInducerParameter::AUXPortOnParameter => String::from("5i"),
             ControlSequenceInducerParameter::AUXPortOffParameter => String::from("4i"),
             ControlSequenceInducerParameter::DeviceStatusReportParameter => String::from("6n"), // TODO: This is probably wrong
             ControlSequenceInducerParameter::SaveCursorPositionParameter => String::from("s"),
             ControlSequenceInducerParameter::RestoreCursorPositionParameter => String::from("u"),
-        }
+            ControlSequenceInducerParameter::PushTitleParameter => String::from("22;0t"),
+            ControlSequenceInducerParameter::PopTitleParameter => String::from("23;0t"),
+            ControlSequenceInducerParameter::SetCursorStyleParameter(cursor_style) => {
+                format!("{} q", cursor_style)
+            }
+            ControlSequenceInducerParameter::ShowCursorParameter => String::from("?25h"),
+            ControlSequenceInducerParameter::HideCursorParameter => String::from("?25l"),
+            ControlSequenceInducerParameter::SetTopBottomMarginsParameter(
+                top_margin,
+                bottom_margin,
+            ) => {
+                format!("{};{}r", top_margin, bottom_margin)
+            }
+            ControlSequenceInducerParameter::ResetTopBottomMarginsParameter => String::from("r"),
+            ControlSequenceInducerParameter::EnableLeftRightMarginModeParameter => {
+                String::from("?69h")
+            }
+            ControlSequenceInducerParameter::DisableLeftRightMarginModeParameter => {
+                String::from("?69l")
+            }
+            ControlSequenceInducerParameter::BeginSynchronizedUpdateParameter => {
+                String::from("?2026h")
+            }
+            ControlSequenceInducerParameter::EndSynchronizedUpdateParameter => {
+                String::from("?2026l")
+            }
+            ControlSequenceInducerParameter::SetLeftRightMarginsParameter(
+                left_margin,
+                right_margin,
+            ) => {
+                format!("{};{}s", left_margin, right_margin)
+            }
+            ControlSequenceInducerParameter::InsertLineParameter(line_count) => {
+                format!("{}L", optional_parameter(*line_count))
+            }
+            ControlSequenceInducerParameter::DeleteLineParameter(line_count) => {
+                format!("{}M", optional_parameter(*line_count))
+            }
+            ControlSequenceInducerParameter::InsertCharacterParameter(character_count) => {
+                format!("{}@", optional_parameter(*character_count))
+            }
+            ControlSequenceInducerParameter::DeleteCharacterParameter(character_count) => {
+                format!("{}P", optional_parameter(*character_count))
+            }
+            ControlSequenceInducerParameter::EraseCharacterParameter(character_count) => {
+                format!("{}X", optional_parameter(*character_count))
+            }
+            ControlSequenceInducerParameter::RepeatParameter(repeat_count) => {
+                format!("{}b", optional_parameter(*repeat_count))
+            }
+            ControlSequenceInducerParameter::ClearTabStopParameter => String::from("0g"),
+            ControlSequenceInducerParameter::ClearAllTabStopsParameter => String::from("3g"),
+            ControlSequenceInducerParameter::CursorForwardTabulationParameter(tab_count) => {
+                format!("{}I", optional_parameter(*tab_count))
+            }
+            ControlSequenceInducerParameter::CursorBackwardTabulationParameter(tab_count) => {
+                format!("{}Z", optional_parameter(*tab_count))
+            }
+            ControlSequenceInducerParameter::CharacterPositionAbsoluteParameter(
+                column_position,
+            ) => {
+                format!("{}`", optional_parameter(*column_position))
+            }
+            ControlSequenceInducerParameter::CharacterPositionForwardParameter(move_count) => {
+                format!("{}a", optional_parameter(*move_count))
+            }
+            ControlSequenceInducerParameter::CharacterPositionBackwardParameter(move_count) => {
+                format!("{}j", optional_parameter(*move_count))
+            }
+            ControlSequenceInducerParameter::VerticalPositionAbsoluteParameter(row_position) => {
+                format!("{}d", optional_parameter(*row_position))
+            }
+            ControlSequenceInducerParameter::VerticalPositionForwardParameter(move_count) => {
+                format!("{}e", optional_parameter(*move_count))
+            }
+            ControlSequenceInducerParameter::VerticalPositionBackwardParameter(move_count) => {
+                format!("{}k", optional_parameter(*move_count))
+            }
+            ControlSequenceInducerParameter::CursorLineTabulationParameter(tab_count) => {
+                format!("{}Y", optional_parameter(*tab_count))
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorStyle {
+    DefaultCursorStyle,
+    BlinkingBlockCursorStyle,
+    SteadyBlockCursorStyle,
+    BlinkingUnderlineCursorStyle,
+    SteadyUnderlineCursorStyle,
+    BlinkingBarCursorStyle,
+    SteadyBarCursorStyle,
+}
+
+impl fmt::Display for CursorStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            CursorStyle::DefaultCursorStyle => String::from("0"),
+            CursorStyle::BlinkingBlockCursorStyle => String::from("1"),
+            CursorStyle::SteadyBlockCursorStyle => String::from("2"),
+            CursorStyle::BlinkingUnderlineCursorStyle => String::from("3"),
+            CursorStyle::SteadyUnderlineCursorStyle => String::from("4"),
+            CursorStyle::BlinkingBarCursorStyle => String::from("5"),
+            CursorStyle::SteadyBarCursorStyle => String::from("6"),
+        };
+        f.write_str(&text)
+    }
+}
+
+// ECMA-48 lets a parameter be omitted when it has its default value, which for cursor movement
+// is 1.
+fn optional_parameter(value: u32) -> String {
+    if value == 1 {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
+
+fn position_parameters(row_position: u32, column_position: u32, final_byte: &str) -> String {
+    if column_position == 1 {
+        format!("{}{}", optional_parameter(row_position), final_byte)
+    } else {
+        format!(
+            "{};{}{}",
+            optional_parameter(row_position),
+            column_position,
+            final_byte
+        )
     }
 }
 
+#[derive(Clone)]
 pub enum SelectGraphicRenditionParameter {
     ResetNormalParameter,
     BoldParameter,
@@ -158,6 +409,10 @@ pub enum SelectGraphicRenditionParameter {
     DecreasedSensitivityParameter,
     ItalicParameter,
     UnderlineParameter,
+    UnderlineStyleOffParameter,
+    CurlyUnderlineParameter,
+    DottedUnderlineParameter,
+    DashedUnderlineParameter,
     SlowBlinkParameter,
     RapidBlinkParameter,
     ReverseVideoParameter,
@@ -165,7 +420,7 @@ pub enum SelectGraphicRenditionParameter {
     CrossedOutParameter,
     PrimaryFontParameter,
     DefaultFontParameter,
-    AlternativeFontParameter(u32), // TODO: x should only be 11-19
+    AlternativeFontParameter(u32), // 11-19, other values are clamped into that range
     FrakturParameter,
     BoldOffParameter,
     DoubleUnderlineParameter,
@@ -178,6 +433,8 @@ pub enum SelectGraphicRenditionParameter {
     InverseOffParameter,
     RevealParameter,
     NotCrossedOutParameter,
+    ProportionalSpacingParameter,
+    NotProportionalSpacingParameter,
     SetForegroundColorParameter(ForegroundColor),
     DefaultForegroundColorParameter,
     SetBackgroundColorParameter(BackgroundColor),
@@ -188,19 +445,28 @@ pub enum SelectGraphicRenditionParameter {
     NotFramedParameter,
     NotEncircledParameter,
     NotOverlinedParameter,
+    SetUnderlineColorParameter(Color),
+    DefaultUnderlineColorParameter,
     IdeogramUnderlineParameter,
     RightSideLineParameter,
-    // IdeogramDoubleUnderlineParameter,
-    // DoubleLIneOnRightSideParameter,
-    // IdeogramStressMarkingParameter,
-    // IdeogramAttributesOffParameter,
+    IdeogramDoubleUnderlineParameter,
+    DoubleLineOnRightSideParameter,
+    IdeogramOverlineParameter,
+    LeftSideLineParameter,
+    IdeogramDoubleOverlineParameter,
+    DoubleLineOnLeftSideParameter,
+    IdeogramStressMarkingParameter,
+    IdeogramAttributesOffParameter,
+    SuperscriptParameter,
+    SubscriptParameter,
+    NotSuperscriptOrSubscriptParameter,
     SetBrightForegroundColorParameter(BrightForegroundColor),
     SetBrightBackgroundColorParameter(BrightBackgroundColor),
 }
 
-impl SelectGraphicRenditionParameter {
-    pub fn to_string(self) -> String {
-        match self {
+impl fmt::Display for SelectGraphicRenditionParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
             SelectGraphicRenditionParameter::ResetNormalParameter => String::from("0"),
             SelectGraphicRenditionParameter::BoldParameter => String::from("1"),
             SelectGraphicRenditionParameter::IncreasedSensitivityParameter => String::from("1"),
@@ -208,6 +474,10 @@ impl SelectGraphicRenditionParameter {
             SelectGraphicRenditionParameter::DecreasedSensitivityParameter => String::from("2"),
             SelectGraphicRenditionParameter::ItalicParameter => String::from("3"),
             SelectGraphicRenditionParameter::UnderlineParameter => String::from("4"),
+            SelectGraphicRenditionParameter::UnderlineStyleOffParameter => String::from("4:0"),
+            SelectGraphicRenditionParameter::CurlyUnderlineParameter => String::from("4:3"),
+            SelectGraphicRenditionParameter::DottedUnderlineParameter => String::from("4:4"),
+            SelectGraphicRenditionParameter::DashedUnderlineParameter => String::from("4:5"),
             SelectGraphicRenditionParameter::SlowBlinkParameter => String::from("5"),
             SelectGraphicRenditionParameter::RapidBlinkParameter => String::from("6"),
             SelectGraphicRenditionParameter::ReverseVideoParameter => String::from("7"),
@@ -215,9 +485,11 @@ impl SelectGraphicRenditionParameter {
             SelectGraphicRenditionParameter::CrossedOutParameter => String::from("9"),
             SelectGraphicRenditionParameter::PrimaryFontParameter => String::from("10"),
             SelectGraphicRenditionParameter::DefaultFontParameter => String::from("10"),
-            SelectGraphicRenditionParameter::AlternativeFontParameter(x) => x.to_string(),
+            SelectGraphicRenditionParameter::AlternativeFontParameter(x) => {
+                (*x).clamp(11, 19).to_string()
+            }
             SelectGraphicRenditionParameter::FrakturParameter => String::from("20"),
-            SelectGraphicRenditionParameter::BoldOffParameter => String::from("21"),
+            SelectGraphicRenditionParameter::BoldOffParameter => String::from("22"),
             SelectGraphicRenditionParameter::DoubleUnderlineParameter => String::from("21"),
             SelectGraphicRenditionParameter::NormalColorParameter => String::from("22"),
             SelectGraphicRenditionParameter::NormalIntensityParameter => String::from("22"),
@@ -225,35 +497,66 @@ impl SelectGraphicRenditionParameter {
             SelectGraphicRenditionParameter::NotFrakturParameter => String::from("23"),
             SelectGraphicRenditionParameter::UnderlineOffParameter => String::from("24"),
             SelectGraphicRenditionParameter::BlinkOffParameter => String::from("25"),
+            SelectGraphicRenditionParameter::ProportionalSpacingParameter => String::from("26"),
             SelectGraphicRenditionParameter::InverseOffParameter => String::from("27"),
             SelectGraphicRenditionParameter::RevealParameter => String::from("28"),
             SelectGraphicRenditionParameter::NotCrossedOutParameter => String::from("29"),
             SelectGraphicRenditionParameter::SetForegroundColorParameter(foreground_color) => {
-                return format!("38;{}", foreground_color.to_string())
+                match foreground_color {
+                    ForegroundColor::ForegroundColor(color) => {
+                        format!("38;{}", color)
+                    }
+                    foreground_color => foreground_color.to_string(),
+                }
             }
             SelectGraphicRenditionParameter::DefaultForegroundColorParameter => String::from("39"),
             SelectGraphicRenditionParameter::SetBackgroundColorParameter(background_color) => {
-                return format!("48;{}", background_color.to_string())
+                match background_color {
+                    BackgroundColor::BackgroundColor(color) => {
+                        format!("48;{}", color)
+                    }
+                    background_color => background_color.to_string(),
+                }
             }
             SelectGraphicRenditionParameter::DefaultBackgroundColorParameter => String::from("49"),
+            SelectGraphicRenditionParameter::NotProportionalSpacingParameter => String::from("50"),
             SelectGraphicRenditionParameter::FramedParameter => String::from("51"),
             SelectGraphicRenditionParameter::EncircledParameter => String::from("52"),
             SelectGraphicRenditionParameter::OverlinedParameter => String::from("53"),
             SelectGraphicRenditionParameter::NotFramedParameter => String::from("54"),
             SelectGraphicRenditionParameter::NotEncircledParameter => String::from("54"),
-            SelectGraphicRenditionParameter::NotOverlinedParameter => String::from("53"),
+            SelectGraphicRenditionParameter::NotOverlinedParameter => String::from("55"),
+            SelectGraphicRenditionParameter::SetUnderlineColorParameter(color) => {
+                format!("58;{}", color)
+            }
+            SelectGraphicRenditionParameter::DefaultUnderlineColorParameter => String::from("59"),
             SelectGraphicRenditionParameter::IdeogramUnderlineParameter => String::from("60"),
+            SelectGraphicRenditionParameter::RightSideLineParameter => String::from("60"),
+            SelectGraphicRenditionParameter::IdeogramDoubleUnderlineParameter => String::from("61"),
+            SelectGraphicRenditionParameter::DoubleLineOnRightSideParameter => String::from("61"),
+            SelectGraphicRenditionParameter::IdeogramOverlineParameter => String::from("62"),
+            SelectGraphicRenditionParameter::LeftSideLineParameter => String::from("62"),
+            SelectGraphicRenditionParameter::IdeogramDoubleOverlineParameter => String::from("63"),
+            SelectGraphicRenditionParameter::DoubleLineOnLeftSideParameter => String::from("63"),
+            SelectGraphicRenditionParameter::IdeogramStressMarkingParameter => String::from("64"),
+            SelectGraphicRenditionParameter::IdeogramAttributesOffParameter => String::from("65"),
+            SelectGraphicRenditionParameter::SuperscriptParameter => String::from("73"),
+            SelectGraphicRenditionParameter::SubscriptParameter => String::from("74"),
+            SelectGraphicRenditionParameter::NotSuperscriptOrSubscriptParameter => {
+                String::from("75")
+            }
             SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
                 foreground_color,
             ) => foreground_color.to_string(),
             SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
                 background_color,
             ) => background_color.to_string(),
-            _ => panic!(), //TODO: Implement the rest
-        }
+        };
+        f.write_str(&text)
     }
 }
 
+#[derive(Clone)]
 pub enum ForegroundColor {
     BlackForeground,
     RedForeground,
@@ -266,9 +569,9 @@ pub enum ForegroundColor {
     ForegroundColor(Color),
 }
 
-impl ForegroundColor {
-    pub fn to_string(self) -> String {
-        match self {
+impl fmt::Display for ForegroundColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
             ForegroundColor::BlackForeground => String::from("30"),
             ForegroundColor::RedForeground => String::from("31"),
             ForegroundColor::GreenForeground => String::from("32"),
@@ -278,10 +581,12 @@ impl ForegroundColor {
             ForegroundColor::CyanForeground => String::from("36"),
             ForegroundColor::WhiteForeground => String::from("37"),
             ForegroundColor::ForegroundColor(color) => color.to_string(),
-        }
+        };
+        f.write_str(&text)
     }
 }
 
+#[derive(Clone)]
 pub enum BrightForegroundColor {
     BrightBlackForeground,
     BrightRedForeground,
@@ -292,21 +597,23 @@ pub enum BrightForegroundColor {
     BrightCyanForeground,
     BrightWhiteForeground,
 }
-impl BrightForegroundColor {
-    pub fn to_string(self) -> String {
-        match self {
-            BrightForegroundColor::BrightBlackForeground => String::from("1;30"),
-            BrightForegroundColor::BrightRedForeground => String::from("1;31"),
-            BrightForegroundColor::BrightGreenForeground => String::from("1;32"),
-            BrightForegroundColor::BrightYellowForeground => String::from("1;33"),
-            BrightForegroundColor::BrightBlueForeground => String::from("1;34"),
-            BrightForegroundColor::BrightMagentaForeground => String::from("1;35"),
-            BrightForegroundColor::BrightCyanForeground => String::from("1;36"),
-            BrightForegroundColor::BrightWhiteForeground => String::from("1;37"),
-        }
+impl fmt::Display for BrightForegroundColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            BrightForegroundColor::BrightBlackForeground => String::from("90"),
+            BrightForegroundColor::BrightRedForeground => String::from("91"),
+            BrightForegroundColor::BrightGreenForeground => String::from("92"),
+            BrightForegroundColor::BrightYellowForeground => String::from("93"),
+            BrightForegroundColor::BrightBlueForeground => String::from("94"),
+            BrightForegroundColor::BrightMagentaForeground => String::from("95"),
+            BrightForegroundColor::BrightCyanForeground => String::from("96"),
+            BrightForegroundColor::BrightWhiteForeground => String::from("97"),
+        };
+        f.write_str(&text)
     }
 }
 
+#[derive(Clone)]
 pub enum BackgroundColor {
     BlackBackground,
     RedBackground,
@@ -319,9 +626,9 @@ pub enum BackgroundColor {
     BackgroundColor(Color),
 }
 
-impl BackgroundColor {
-    pub fn to_string(self) -> String {
-        match self {
+impl fmt::Display for BackgroundColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
             BackgroundColor::BlackBackground => String::from("40"),
             BackgroundColor::RedBackground => String::from("41"),
             BackgroundColor::GreenBackground => String::from("42"),
@@ -331,10 +638,12 @@ impl BackgroundColor {
             BackgroundColor::CyanBackground => String::from("46"),
             BackgroundColor::WhiteBackground => String::from("47"),
             BackgroundColor::BackgroundColor(color) => color.to_string(),
-        }
+        };
+        f.write_str(&text)
     }
 }
 
+#[derive(Clone)]
 pub enum BrightBackgroundColor {
     BrightBlackBackground,
     BrightRedBackground,
@@ -346,9 +655,9 @@ pub enum BrightBackgroundColor {
     BrightWhiteBackground,
 }
 
-impl BrightBackgroundColor {
-    pub fn to_string(self) -> String {
-        match self {
+impl fmt::Display for BrightBackgroundColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
             BrightBackgroundColor::BrightBlackBackground => String::from("100"),
             BrightBackgroundColor::BrightRedBackground => String::from("101"),
             BrightBackgroundColor::BrightGreenBackground => String::from("102"),
@@ -357,7 +666,8 @@ impl BrightBackgroundColor {
             BrightBackgroundColor::BrightMagentaBackground => String::from("105"),
             BrightBackgroundColor::BrightCyanBackground => String::from("106"),
             BrightBackgroundColor::BrightWhiteBackground => String::from("107"),
-        }
+        };
+        f.write_str(&text)
     }
 }
 
@@ -366,22 +676,235 @@ pub type RedColorCode = ColorCode;
 pub type GreenColorCode = ColorCode;
 pub type BlueColorCode = ColorCode;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Color {
     Color256(ColorCode), // TODO: Validate only integers 1-256
     RGB(RedColorCode, GreenColorCode, BlueColorCode), // TODO: Validate only integers 1-256
 }
 
 impl Color {
-    pub fn to_string(self) -> String {
+    // 256-color codes are resolved against xterm's default palette.
+    pub fn to_rgb(self) -> (RedColorCode, GreenColorCode, BlueColorCode) {
         match self {
-            Color::Color256(color_code) => format!("5;{}", color_code.to_string()),
+            Color::RGB(red_color_code, green_color_code, blue_color_code) => {
+                (red_color_code, green_color_code, blue_color_code)
+            }
+            Color::Color256(color_code) => match color_code {
+                0..=15 => XTERM_SYSTEM_COLORS[color_code as usize],
+                16..=231 => {
+                    let index = color_code - 16;
+                    let level = |value: u32| if value == 0 { 0 } else { 55 + value * 40 };
+                    (level(index / 36), level(index / 6 % 6), level(index % 6))
+                }
+                _ => {
+                    let gray = 8 + 10 * (color_code.min(255) - 232);
+                    (gray, gray, gray)
+                }
+            },
+        }
+    }
+
+    // Nearest entry of xterm's 6x6x6 color cube or gray ramp. The first 16 codes are left out
+    // since terminals commonly redefine them.
+    pub fn to_color256(self) -> Color {
+        let (red, green, blue) = clamped_rgb(self);
+        let cube_index = |value: u32| -> u32 {
+            if value < 48 {
+                0
+            } else if value < 115 {
+                1
+            } else {
+                (value - 35) / 40
+            }
+        };
+        let (red_index, green_index, blue_index) =
+            (cube_index(red), cube_index(green), cube_index(blue));
+        let cube = Color::Color256(16 + 36 * red_index + 6 * green_index + blue_index);
+        let average = (red + green + blue) / 3;
+        let gray = Color::Color256(if average > 238 {
+            255
+        } else {
+            232 + average.saturating_sub(3) / 10
+        });
+        if color_distance(self, gray) < color_distance(self, cube) {
+            gray
+        } else {
+            cube
+        }
+    }
+
+    // Index (0-15) of the nearest of the eight normal and eight bright system colors.
+    pub fn to_system_color(self) -> ColorCode {
+        (0..16)
+            .min_by_key(|&color_code| color_distance(self, Color::Color256(color_code)))
+            .unwrap_or(0)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            Color::Color256(color_code) => format!("5;{}", color_code),
             Color::RGB(red_color_code, green_color_code, blue_color_code) => format!(
                 "2;{};{};{}",
-                red_color_code.to_string(),
-                green_color_code.to_string(),
-                blue_color_code.to_string()
+                red_color_code, green_color_code, blue_color_code
             ),
+        };
+        f.write_str(&text)
+    }
+}
+
+// Components above 255 are treated as 255 so the arithmetic below cannot overflow.
+fn clamped_rgb(color: Color) -> (RedColorCode, GreenColorCode, BlueColorCode) {
+    let (red, green, blue) = color.to_rgb();
+    (red.min(255), green.min(255), blue.min(255))
+}
+
+fn color_distance(first: Color, second: Color) -> u32 {
+    let (first_red, first_green, first_blue) = clamped_rgb(first);
+    let (second_red, second_green, second_blue) = clamped_rgb(second);
+    let difference = |a: u32, b: u32| (a as i64 - b as i64).pow(2) as u32;
+    difference(first_red, second_red)
+        + difference(first_green, second_green)
+        + difference(first_blue, second_blue)
+}
+
+// Picks the short 30-37 and 90-97 codes for the system colors.
+pub(crate) fn foreground_parameter(color: Color) -> SelectGraphicRenditionParameter {
+    let named = match color {
+        Color::Color256(0) => ForegroundColor::BlackForeground,
+        Color::Color256(1) => ForegroundColor::RedForeground,
+        Color::Color256(2) => ForegroundColor::GreenForeground,
+        Color::Color256(3) => ForegroundColor::YellowForeground,
+        Color::Color256(4) => ForegroundColor::BlueForeground,
+        Color::Color256(5) => ForegroundColor::MagentaForeground,
+        Color::Color256(6) => ForegroundColor::CyanForeground,
+        Color::Color256(7) => ForegroundColor::WhiteForeground,
+        Color::Color256(color_code @ 8..=15) => {
+            let bright = match color_code {
+                8 => BrightForegroundColor::BrightBlackForeground,
+                9 => BrightForegroundColor::BrightRedForeground,
+                10 => BrightForegroundColor::BrightGreenForeground,
+                11 => BrightForegroundColor::BrightYellowForeground,
+                12 => BrightForegroundColor::BrightBlueForeground,
+                13 => BrightForegroundColor::BrightMagentaForeground,
+                14 => BrightForegroundColor::BrightCyanForeground,
+                _ => BrightForegroundColor::BrightWhiteForeground,
+            };
+            return SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(bright);
+        }
+        color => ForegroundColor::ForegroundColor(color),
+    };
+    SelectGraphicRenditionParameter::SetForegroundColorParameter(named)
+}
+
+// Picks the short 40-47 and 100-107 codes for the system colors.
+pub(crate) fn background_parameter(color: Color) -> SelectGraphicRenditionParameter {
+    let named = match color {
+        Color::Color256(0) => BackgroundColor::BlackBackground,
+        Color::Color256(1) => BackgroundColor::RedBackground,
+        Color::Color256(2) => BackgroundColor::GreenBackground,
+        Color::Color256(3) => BackgroundColor::YellowBackground,
+        Color::Color256(4) => BackgroundColor::BlueBackground,
+        Color::Color256(5) => BackgroundColor::MagentaBackground,
+        Color::Color256(6) => BackgroundColor::CyanBackground,
+        Color::Color256(7) => BackgroundColor::WhiteBackground,
+        Color::Color256(color_code @ 8..=15) => {
+            let bright = match color_code {
+                8 => BrightBackgroundColor::BrightBlackBackground,
+                9 => BrightBackgroundColor::BrightRedBackground,
+                10 => BrightBackgroundColor::BrightGreenBackground,
+                11 => BrightBackgroundColor::BrightYellowBackground,
+                12 => BrightBackgroundColor::BrightBlueBackground,
+                13 => BrightBackgroundColor::BrightMagentaBackground,
+                14 => BrightBackgroundColor::BrightCyanBackground,
+                _ => BrightBackgroundColor::BrightWhiteBackground,
+            };
+            return SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(bright);
         }
+        color => BackgroundColor::BackgroundColor(color),
+    };
+    SelectGraphicRenditionParameter::SetBackgroundColorParameter(named)
+}
+
+const XTERM_SYSTEM_COLORS: [(RedColorCode, GreenColorCode, BlueColorCode); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// ShiftInCode invokes G0 and ShiftOutCode invokes G1; G2 and G3 are reached with the single
+// shift sequences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicSet {
+    G0GraphicSet,
+    G1GraphicSet,
+    G2GraphicSet,
+    G3GraphicSet,
+}
+
+impl fmt::Display for GraphicSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            GraphicSet::G0GraphicSet => String::from("("),
+            GraphicSet::G1GraphicSet => String::from(")"),
+            GraphicSet::G2GraphicSet => String::from("*"),
+            GraphicSet::G3GraphicSet => String::from("+"),
+        };
+        f.write_str(&text)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharacterSet {
+    DECSpecialGraphicsCharacterSet,
+    DECSupplementalCharacterSet,
+    USASCIICharacterSet,
+    UnitedKingdomCharacterSet,
+    DutchCharacterSet,
+    FinnishCharacterSet,
+    FrenchCharacterSet,
+    FrenchCanadianCharacterSet,
+    GermanCharacterSet,
+    ItalianCharacterSet,
+    NorwegianDanishCharacterSet,
+    SpanishCharacterSet,
+    SwedishCharacterSet,
+    SwissCharacterSet,
+}
+
+impl fmt::Display for CharacterSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            CharacterSet::DECSpecialGraphicsCharacterSet => String::from("0"),
+            CharacterSet::DECSupplementalCharacterSet => String::from("<"),
+            CharacterSet::USASCIICharacterSet => String::from("B"),
+            CharacterSet::UnitedKingdomCharacterSet => String::from("A"),
+            CharacterSet::DutchCharacterSet => String::from("4"),
+            CharacterSet::FinnishCharacterSet => String::from("C"),
+            CharacterSet::FrenchCharacterSet => String::from("R"),
+            CharacterSet::FrenchCanadianCharacterSet => String::from("Q"),
+            CharacterSet::GermanCharacterSet => String::from("K"),
+            CharacterSet::ItalianCharacterSet => String::from("Y"),
+            CharacterSet::NorwegianDanishCharacterSet => String::from("E"),
+            CharacterSet::SpanishCharacterSet => String::from("Z"),
+            CharacterSet::SwedishCharacterSet => String::from("H"),
+            CharacterSet::SwissCharacterSet => String::from("="),
+        };
+        f.write_str(&text)
     }
 }
 
@@ -412,274 +935,591 @@ pub enum EscapeSequence {
     RestoreCursorPositionSequence,
     ResetColorsSequence,
     ResetAttributesSequence,
+    PushTitleSequence,
+    PopTitleSequence,
+    SetCursorStyleSequence(CursorStyle),
+    ShowCursorSequence,
+    HideCursorSequence,
+    SetTopBottomMarginsSequence(u32, u32),
+    ResetTopBottomMarginsSequence,
+    EnableLeftRightMarginModeSequence,
+    DisableLeftRightMarginModeSequence,
+    BeginSynchronizedUpdateSequence,
+    EndSynchronizedUpdateSequence,
+    SetLeftRightMarginsSequence(u32, u32),
+    InsertLineSequence(u32),
+    DeleteLineSequence(u32),
+    InsertCharacterSequence(u32),
+    DeleteCharacterSequence(u32),
+    EraseCharacterSequence(u32),
+    RepeatSequence(u32),
+    ReverseIndexSequence,
+    HorizontalTabSetSequence,
+    DesignateCharacterSetSequence(GraphicSet, CharacterSet),
+    SingleShiftTwoSequence,
+    SingleShiftThreeSequence,
+    ClearTabStopSequence,
+    ClearAllTabStopsSequence,
+    CursorForwardTabulationSequence(u32),
+    CursorBackwardTabulationSequence(u32),
+    CharacterPositionAbsoluteSequence(u32),
+    CharacterPositionForwardSequence(u32),
+    CharacterPositionBackwardSequence(u32),
+    VerticalPositionAbsoluteSequence(u32),
+    VerticalPositionForwardSequence(u32),
+    VerticalPositionBackwardSequence(u32),
+    CursorLineTabulationSequence(u32),
+    OperatingSystemCommandSequence(OperatingSystemCommandParameter),
 }
 
-impl EscapeSequence {
-    pub fn to_string(self) -> String {
-        match self {
+impl fmt::Display for EscapeSequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
             EscapeSequence::CursorUpSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::CursorUpParameter(move_count)
+                        ControlSequenceInducerParameter::CursorUpParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::CursorDownSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::CursorDownParameter(move_count)
+                        ControlSequenceInducerParameter::CursorDownParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::CursorForwardSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::CursorForwardParameter(move_count)
+                        ControlSequenceInducerParameter::CursorForwardParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::CursorBackSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::CursorBackParameter(move_count)
+                        ControlSequenceInducerParameter::CursorBackParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::CursorNextLineSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::CursorNextLineParameter(move_count)
+                        ControlSequenceInducerParameter::CursorNextLineParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::CursorPreviousLineSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::CursorPreviousLineParameter(move_count)
+                        ControlSequenceInducerParameter::CursorPreviousLineParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::CursorHorizontalAbsoluteSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::CursorHorizontalAbsoluteParameter(
-                            move_count
+                            *move_count
                         )
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::CursorPositionSequence(row_position, column_position) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::CursorPositionParameter(
-                            row_position,
-                            column_position
+                            *row_position,
+                            *column_position
                         )
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::EraseScreenAfterCursorSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::EraseScreenAfterCursorParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::EraseScreenBeforeCursorSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::EraseScreenBeforeCursorParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::EraseScreenSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::EraseScreenParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::EraseScreenAndScrollbackSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::EraseScreenAndScrollbackParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::EraseLineAfterCursorSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::EraseLineAfterCursorParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::EraseLineBeforeCursorSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::EraseLineBeforeCursorParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::EraseLineSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::EraseLineParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::ScrollUpSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::ScrollUpParameter(move_count)
+                        ControlSequenceInducerParameter::ScrollUpParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::ScrollDownSequence(move_count) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
-                        ControlSequenceInducerParameter::ScrollDownParameter(move_count)
+                        ControlSequenceInducerParameter::ScrollDownParameter(*move_count)
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::HorizontalVerticalPositionSequence(row_position, column_position) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::HorizontalVerticalPositionParameter(
-                            row_position,
-                            column_position
+                            *row_position,
+                            *column_position
                         )
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::SelectGraphicRenditionSequence(params) => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::SelectGraphicRenditionSequenceParameter(
-                            params
+                            params.clone()
                         )
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::AUXPortOnSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::AUXPortOnParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::AUXPortOffSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::AUXPortOffParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::DeviceStatusReportSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::DeviceStatusReportParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::SaveCursorPositionSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::SaveCursorPositionParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::RestoreCursorPositionSequence => {
-                return format!(
+                format!(
                     "{}{}",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
+                    ControlSequencePart::ControlSequenceInducer,
                     ControlSequencePart::ControlSequenceInducerParameter(
                         ControlSequenceInducerParameter::RestoreCursorPositionParameter
                     )
-                    .to_string()
-                );
+                )
             }
             EscapeSequence::ResetColorsSequence => {
-                return format!(
-                    "{}0m",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
-                );
+                format!("{}0m", ControlSequencePart::ControlSequenceInducer,)
             }
             EscapeSequence::ResetAttributesSequence => {
-                return format!(
-                    "{}39;49m",
-                    ControlSequencePart::ControlSequenceInducer.to_string(),
-                );
+                format!("{}39;49m", ControlSequencePart::ControlSequenceInducer,)
             }
-        }
+            EscapeSequence::PushTitleSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::PushTitleParameter
+                    )
+                )
+            }
+            EscapeSequence::PopTitleSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::PopTitleParameter
+                    )
+                )
+            }
+            EscapeSequence::SetCursorStyleSequence(cursor_style) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::SetCursorStyleParameter(*cursor_style)
+                    )
+                )
+            }
+            EscapeSequence::ShowCursorSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::ShowCursorParameter
+                    )
+                )
+            }
+            EscapeSequence::HideCursorSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::HideCursorParameter
+                    )
+                )
+            }
+            EscapeSequence::SetTopBottomMarginsSequence(top_margin, bottom_margin) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::SetTopBottomMarginsParameter(
+                            *top_margin,
+                            *bottom_margin
+                        )
+                    )
+                )
+            }
+            EscapeSequence::ResetTopBottomMarginsSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::ResetTopBottomMarginsParameter
+                    )
+                )
+            }
+            EscapeSequence::EnableLeftRightMarginModeSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::EnableLeftRightMarginModeParameter
+                    )
+                )
+            }
+            EscapeSequence::DisableLeftRightMarginModeSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::DisableLeftRightMarginModeParameter
+                    )
+                )
+            }
+            EscapeSequence::BeginSynchronizedUpdateSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::BeginSynchronizedUpdateParameter
+                    )
+                )
+            }
+            EscapeSequence::EndSynchronizedUpdateSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::EndSynchronizedUpdateParameter
+                    )
+                )
+            }
+            EscapeSequence::SetLeftRightMarginsSequence(left_margin, right_margin) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::SetLeftRightMarginsParameter(
+                            *left_margin,
+                            *right_margin
+                        )
+                    )
+                )
+            }
+            EscapeSequence::InsertLineSequence(line_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::InsertLineParameter(*line_count)
+                    )
+                )
+            }
+            EscapeSequence::DeleteLineSequence(line_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::DeleteLineParameter(*line_count)
+                    )
+                )
+            }
+            EscapeSequence::InsertCharacterSequence(character_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::InsertCharacterParameter(*character_count)
+                    )
+                )
+            }
+            EscapeSequence::DeleteCharacterSequence(character_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::DeleteCharacterParameter(*character_count)
+                    )
+                )
+            }
+            EscapeSequence::EraseCharacterSequence(character_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::EraseCharacterParameter(*character_count)
+                    )
+                )
+            }
+            EscapeSequence::RepeatSequence(repeat_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::RepeatParameter(*repeat_count)
+                    )
+                )
+            }
+            EscapeSequence::ReverseIndexSequence => {
+                format!("{}M", ASCIIControlCode::EscapeCode)
+            }
+            EscapeSequence::ClearTabStopSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::ClearTabStopParameter
+                    )
+                )
+            }
+            EscapeSequence::ClearAllTabStopsSequence => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::ClearAllTabStopsParameter
+                    )
+                )
+            }
+            EscapeSequence::CursorForwardTabulationSequence(tab_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::CursorForwardTabulationParameter(
+                            *tab_count
+                        )
+                    )
+                )
+            }
+            EscapeSequence::CursorBackwardTabulationSequence(tab_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::CursorBackwardTabulationParameter(
+                            *tab_count
+                        )
+                    )
+                )
+            }
+            EscapeSequence::HorizontalTabSetSequence => {
+                format!("{}H", ASCIIControlCode::EscapeCode)
+            }
+            EscapeSequence::DesignateCharacterSetSequence(graphic_set, character_set) => {
+                format!(
+                    "{}{}{}",
+                    ASCIIControlCode::EscapeCode,
+                    graphic_set,
+                    character_set
+                )
+            }
+            EscapeSequence::SingleShiftTwoSequence => {
+                format!("{}N", ASCIIControlCode::EscapeCode)
+            }
+            EscapeSequence::SingleShiftThreeSequence => {
+                format!("{}O", ASCIIControlCode::EscapeCode)
+            }
+            EscapeSequence::CharacterPositionAbsoluteSequence(column_position) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::CharacterPositionAbsoluteParameter(
+                            *column_position
+                        )
+                    )
+                )
+            }
+            EscapeSequence::CharacterPositionForwardSequence(move_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::CharacterPositionForwardParameter(
+                            *move_count
+                        )
+                    )
+                )
+            }
+            EscapeSequence::CharacterPositionBackwardSequence(move_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::CharacterPositionBackwardParameter(
+                            *move_count
+                        )
+                    )
+                )
+            }
+            EscapeSequence::VerticalPositionAbsoluteSequence(row_position) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::VerticalPositionAbsoluteParameter(
+                            *row_position
+                        )
+                    )
+                )
+            }
+            EscapeSequence::VerticalPositionForwardSequence(move_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::VerticalPositionForwardParameter(
+                            *move_count
+                        )
+                    )
+                )
+            }
+            EscapeSequence::VerticalPositionBackwardSequence(move_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::VerticalPositionBackwardParameter(
+                            *move_count
+                        )
+                    )
+                )
+            }
+            EscapeSequence::CursorLineTabulationSequence(tab_count) => {
+                format!(
+                    "{}{}",
+                    ControlSequencePart::ControlSequenceInducer,
+                    ControlSequencePart::ControlSequenceInducerParameter(
+                        ControlSequenceInducerParameter::CursorLineTabulationParameter(*tab_count)
+                    )
+                )
+            }
+            EscapeSequence::OperatingSystemCommandSequence(param) => {
+                format!(
+                    "{}{}{}",
+                    OperatingSystemCommandPart::OperatingSystemCommandIntroducer,
+                    param,
+                    OperatingSystemCommandPart::StringTerminator
+                )
+            }
+        };
+        f.write_str(&text)
     }
 }
 
@@ -689,26 +1529,130 @@ pub enum ControlSequencePart {
     ControlSequenceInducerParameter(ControlSequenceInducerParameter),
 }
 
-impl ControlSequencePart {
-    pub fn to_string(self) -> String {
-        match self {
+impl fmt::Display for ControlSequencePart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
             ControlSequencePart::ControlSequenceInducer => {
-                format!("{}[", ASCIIControlCode::EscapeCode.to_string())
+                format!("{}[", ASCIIControlCode::EscapeCode)
+            }
+            ControlSequencePart::ControlSequenceInducerParameter(param) => param.to_string(),
+        };
+        f.write_str(&text)
+    }
+}
+
+// defined as an escape character followed immediately by a right bracket: ESC], and ended
+// by the string terminator ESC\.
+pub enum OperatingSystemCommandPart {
+    OperatingSystemCommandIntroducer,
+    StringTerminator,
+}
+
+impl fmt::Display for OperatingSystemCommandPart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            OperatingSystemCommandPart::OperatingSystemCommandIntroducer => {
+                format!("{}]", ASCIIControlCode::EscapeCode)
+            }
+            OperatingSystemCommandPart::StringTerminator => {
+                format!("{}\\", ASCIIControlCode::EscapeCode)
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+// defined as an escape character followed immediately by a P: ESCP, and ended by the string
+// terminator ESC\.
+pub enum DeviceControlStringPart {
+    DeviceControlStringIntroducer,
+    StringTerminator,
+}
+
+impl fmt::Display for DeviceControlStringPart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            DeviceControlStringPart::DeviceControlStringIntroducer => {
+                format!("{}P", ASCIIControlCode::EscapeCode)
             }
-            ControlSequencePart::ControlSequenceInducerParameter(param) => {
-                format!("{}", param.to_string())
+            DeviceControlStringPart::StringTerminator => {
+                OperatingSystemCommandPart::StringTerminator.to_string()
             }
-            _ => String::from("ERROR"),
+        };
+        f.write_str(&text)
+    }
+}
+
+// defined as an escape character followed immediately by an underscore: ESC_, and ended by the
+// string terminator ESC\.
+pub enum ApplicationProgramCommandPart {
+    ApplicationProgramCommandIntroducer,
+    StringTerminator,
+}
+
+impl fmt::Display for ApplicationProgramCommandPart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            ApplicationProgramCommandPart::ApplicationProgramCommandIntroducer => {
+                format!("{}_", ASCIIControlCode::EscapeCode)
+            }
+            ApplicationProgramCommandPart::StringTerminator => {
+                OperatingSystemCommandPart::StringTerminator.to_string()
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+// An image buffer holding fewer values than its width and height call for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferSizeError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl BufferSizeError {
+    // `values_per_pixel` is e.g. 4 for RGBA. A size too large to count is never satisfied.
+    pub(crate) fn check(
+        buffer_len: usize,
+        width: usize,
+        height: usize,
+        values_per_pixel: usize,
+    ) -> Result<(), BufferSizeError> {
+        let expected = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(values_per_pixel))
+            .unwrap_or(usize::MAX);
+        if buffer_len < expected {
+            Err(BufferSizeError {
+                expected,
+                actual: buffer_len,
+            })
+        } else {
+            Ok(())
         }
     }
 }
 
+impl fmt::Display for BufferSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "image buffer holds {} values but {} are needed",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for BufferSizeError {}
+
 // type ControlSequence = (ASCIIControlCode::EscapeCode, String)
 
 //pub fn get_string(sequence: ASCIIControlCode, CSIParameter, SelectGraphicRenditionParameter) {
 //match sequence
 //}
 
+#[allow(non_snake_case)]
 pub fn ASCIIControlCode_string(code: ASCIIControlCode) -> String {
     match code {
         ASCIIControlCode::EscapeCode => String::from("\x1b"),
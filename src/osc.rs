@@ -0,0 +1,588 @@
+use crate::{base64, parser, Color, ColorCode, EscapeSequence};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hyperlink {
+    pub uri: String,
+    pub id: Option<String>,
+}
+
+impl Hyperlink {
+    pub fn new(uri: &str) -> Hyperlink {
+        Hyperlink {
+            uri: String::from(uri),
+            id: None,
+        }
+    }
+
+    // Terminals treat cells sharing an id as one link, e.g. when a link is split across lines.
+    pub fn with_id(mut self, id: &str) -> Hyperlink {
+        self.id = Some(String::from(id));
+        self
+    }
+
+    pub fn link(self, text: &str) -> LinkedText {
+        LinkedText {
+            hyperlink: self,
+            text: String::from(text),
+        }
+    }
+}
+
+// Text wrapped in an OSC 8 open/close pair.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkedText {
+    pub hyperlink: Hyperlink,
+    pub text: String,
+}
+
+impl fmt::Display for LinkedText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            EscapeSequence::OperatingSystemCommandSequence(
+                OperatingSystemCommandParameter::HyperlinkStartParameter(self.hyperlink.clone())
+            ),
+            self.text,
+            EscapeSequence::OperatingSystemCommandSequence(
+                OperatingSystemCommandParameter::HyperlinkEndParameter
+            )
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageDimension {
+    AutoDimension,
+    CellsDimension(u32),
+    PixelsDimension(u32),
+    PercentDimension(u32),
+}
+
+impl ImageDimension {
+    fn parse(value: &str) -> Option<ImageDimension> {
+        if value == "auto" {
+            Some(ImageDimension::AutoDimension)
+        } else if let Some(pixels) = value.strip_suffix("px") {
+            Some(ImageDimension::PixelsDimension(pixels.parse().ok()?))
+        } else if let Some(percent) = value.strip_suffix('%') {
+            Some(ImageDimension::PercentDimension(percent.parse().ok()?))
+        } else {
+            Some(ImageDimension::CellsDimension(value.parse().ok()?))
+        }
+    }
+}
+
+impl fmt::Display for ImageDimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            ImageDimension::AutoDimension => String::from("auto"),
+            ImageDimension::CellsDimension(cells) => cells.to_string(),
+            ImageDimension::PixelsDimension(pixels) => format!("{}px", pixels),
+            ImageDimension::PercentDimension(percent) => format!("{}%", percent),
+        };
+        f.write_str(&text)
+    }
+}
+
+// A file for iTerm2's inline images protocol. Any format macOS can decode is accepted, the
+// terminal detects it from the data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlineImage {
+    pub data: Vec<u8>,
+    pub name: Option<String>,
+    pub width: ImageDimension,
+    pub height: ImageDimension,
+    pub preserve_aspect_ratio: bool,
+    pub inline: bool,
+}
+
+impl InlineImage {
+    pub fn new(data: Vec<u8>) -> InlineImage {
+        InlineImage {
+            data,
+            name: None,
+            width: ImageDimension::AutoDimension,
+            height: ImageDimension::AutoDimension,
+            preserve_aspect_ratio: true,
+            inline: true,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> InlineImage {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    pub fn with_width(mut self, width: ImageDimension) -> InlineImage {
+        self.width = width;
+        self
+    }
+
+    pub fn with_height(mut self, height: ImageDimension) -> InlineImage {
+        self.height = height;
+        self
+    }
+
+    pub fn with_preserve_aspect_ratio(mut self, preserve_aspect_ratio: bool) -> InlineImage {
+        self.preserve_aspect_ratio = preserve_aspect_ratio;
+        self
+    }
+
+    // When false the terminal offers the file as a download instead of displaying it.
+    pub fn with_inline(mut self, inline: bool) -> InlineImage {
+        self.inline = inline;
+        self
+    }
+
+    fn parse(file: &str) -> Option<InlineImage> {
+        let (arguments, data) = file.split_once(':')?;
+        let mut image = InlineImage::new(base64::decode(data)?);
+        image.inline = false;
+        for argument in arguments.split(';') {
+            let (key, value) = argument.split_once('=')?;
+            match key {
+                "name" => image.name = String::from_utf8(base64::decode(value)?).ok(),
+                "width" => image.width = ImageDimension::parse(value)?,
+                "height" => image.height = ImageDimension::parse(value)?,
+                "preserveAspectRatio" => image.preserve_aspect_ratio = value != "0",
+                "inline" => image.inline = value == "1",
+                _ => {}
+            }
+        }
+        Some(image)
+    }
+}
+
+impl fmt::Display for InlineImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut arguments = Vec::new();
+        if let Some(name) = &self.name {
+            arguments.push(format!("name={}", base64::encode(name.as_bytes())));
+        }
+        arguments.push(format!("size={}", self.data.len()));
+        arguments.push(format!("width={}", self.width));
+        arguments.push(format!("height={}", self.height));
+        arguments.push(format!(
+            "preserveAspectRatio={}",
+            self.preserve_aspect_ratio as u8
+        ));
+        arguments.push(format!("inline={}", self.inline as u8));
+        write!(
+            f,
+            "File={}:{}",
+            arguments.join(";"),
+            base64::encode(&self.data)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionTarget {
+    ClipboardTarget,
+    PrimarySelectionTarget,
+    SecondarySelectionTarget,
+    SelectSelectionTarget,
+}
+
+impl fmt::Display for SelectionTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            SelectionTarget::ClipboardTarget => String::from("c"),
+            SelectionTarget::PrimarySelectionTarget => String::from("p"),
+            SelectionTarget::SecondarySelectionTarget => String::from("q"),
+            SelectionTarget::SelectSelectionTarget => String::from("s"),
+        };
+        f.write_str(&text)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OperatingSystemCommandParameter {
+    HyperlinkStartParameter(Hyperlink),
+    HyperlinkEndParameter,
+    SetIconNameAndWindowTitleParameter(String),
+    SetIconNameParameter(String),
+    SetWindowTitleParameter(String),
+    CurrentWorkingDirectoryParameter(String, String), // host, path
+    SetClipboardParameter(Vec<SelectionTarget>, String),
+    QueryClipboardParameter(Vec<SelectionTarget>),
+    SetPaletteColorParameter(ColorCode, Color),
+    QueryPaletteColorParameter(ColorCode),
+    ResetPaletteColorParameter(Option<ColorCode>), // None resets every entry
+    SetDefaultForegroundColorParameter(Color),
+    QueryDefaultForegroundColorParameter,
+    ResetDefaultForegroundColorParameter,
+    SetDefaultBackgroundColorParameter(Color),
+    QueryDefaultBackgroundColorParameter,
+    ResetDefaultBackgroundColorParameter,
+    SetCursorColorParameter(Color),
+    QueryCursorColorParameter,
+    ResetCursorColorParameter,
+    InlineImageParameter(InlineImage),
+    SetMarkParameter,
+    CurrentDirectoryParameter(String), // iTerm2's counterpart of CurrentWorkingDirectoryParameter
+    SetUserVariableParameter(String, String), // name, value
+}
+
+impl OperatingSystemCommandParameter {
+    // Reads the payload of an OSC, i.e. everything between the introducer and the terminator,
+    // as found in parser::Segment::OperatingSystemCommandSegment.
+    pub fn parse(payload: &str) -> Option<OperatingSystemCommandParameter> {
+        let (command, rest) = payload.split_once(';').unwrap_or((payload, ""));
+        match command {
+            "8" => {
+                let (params, uri) = rest.split_once(';')?;
+                if uri.is_empty() {
+                    return Some(OperatingSystemCommandParameter::HyperlinkEndParameter);
+                }
+                let id = params
+                    .split(':')
+                    .find_map(|param| param.strip_prefix("id="))
+                    .map(percent_decode);
+                Some(OperatingSystemCommandParameter::HyperlinkStartParameter(
+                    Hyperlink {
+                        uri: String::from(uri),
+                        id,
+                    },
+                ))
+            }
+            "0" => Some(
+                OperatingSystemCommandParameter::SetIconNameAndWindowTitleParameter(String::from(
+                    rest,
+                )),
+            ),
+            "1" => Some(OperatingSystemCommandParameter::SetIconNameParameter(
+                String::from(rest),
+            )),
+            "2" => Some(OperatingSystemCommandParameter::SetWindowTitleParameter(
+                String::from(rest),
+            )),
+            "7" => {
+                let location = rest.strip_prefix("file://")?;
+                let (host, path) = match location.find('/') {
+                    Some(index) => location.split_at(index),
+                    None => (location, ""),
+                };
+                Some(
+                    OperatingSystemCommandParameter::CurrentWorkingDirectoryParameter(
+                        percent_decode(host),
+                        percent_decode(path),
+                    ),
+                )
+            }
+            // The terminal answers a clipboard query with the same form used to set it.
+            "52" => {
+                let (targets, data) = rest.split_once(';')?;
+                let targets = targets
+                    .chars()
+                    .filter_map(|target| match target {
+                        'c' => Some(SelectionTarget::ClipboardTarget),
+                        'p' => Some(SelectionTarget::PrimarySelectionTarget),
+                        'q' => Some(SelectionTarget::SecondarySelectionTarget),
+                        's' => Some(SelectionTarget::SelectSelectionTarget),
+                        _ => None,
+                    })
+                    .collect();
+                if data == "?" {
+                    return Some(OperatingSystemCommandParameter::QueryClipboardParameter(
+                        targets,
+                    ));
+                }
+                let text = String::from_utf8(base64::decode(data)?).ok()?;
+                Some(OperatingSystemCommandParameter::SetClipboardParameter(
+                    targets, text,
+                ))
+            }
+            // Replies to palette queries use the same form as setting the color.
+            "4" => {
+                let mut fields = rest.split(';');
+                let color_code = fields.next()?.parse().ok()?;
+                match fields.next()? {
+                    "?" => Some(OperatingSystemCommandParameter::QueryPaletteColorParameter(
+                        color_code,
+                    )),
+                    spec => Some(OperatingSystemCommandParameter::SetPaletteColorParameter(
+                        color_code,
+                        parse_color_spec(spec)?,
+                    )),
+                }
+            }
+            "104" if rest.is_empty() => Some(
+                OperatingSystemCommandParameter::ResetPaletteColorParameter(None),
+            ),
+            "104" => Some(OperatingSystemCommandParameter::ResetPaletteColorParameter(
+                Some(rest.parse().ok()?),
+            )),
+            "10" if rest == "?" => {
+                Some(OperatingSystemCommandParameter::QueryDefaultForegroundColorParameter)
+            }
+            "10" => Some(
+                OperatingSystemCommandParameter::SetDefaultForegroundColorParameter(
+                    parse_color_spec(rest)?,
+                ),
+            ),
+            "110" => Some(OperatingSystemCommandParameter::ResetDefaultForegroundColorParameter),
+            "11" if rest == "?" => {
+                Some(OperatingSystemCommandParameter::QueryDefaultBackgroundColorParameter)
+            }
+            "11" => Some(
+                OperatingSystemCommandParameter::SetDefaultBackgroundColorParameter(
+                    parse_color_spec(rest)?,
+                ),
+            ),
+            "111" => Some(OperatingSystemCommandParameter::ResetDefaultBackgroundColorParameter),
+            "12" if rest == "?" => Some(OperatingSystemCommandParameter::QueryCursorColorParameter),
+            "12" => Some(OperatingSystemCommandParameter::SetCursorColorParameter(
+                parse_color_spec(rest)?,
+            )),
+            "112" => Some(OperatingSystemCommandParameter::ResetCursorColorParameter),
+            "1337" => {
+                if rest == "SetMark" {
+                    Some(OperatingSystemCommandParameter::SetMarkParameter)
+                } else if let Some(file) = rest.strip_prefix("File=") {
+                    Some(OperatingSystemCommandParameter::InlineImageParameter(
+                        InlineImage::parse(file)?,
+                    ))
+                } else if let Some(directory) = rest.strip_prefix("CurrentDir=") {
+                    Some(OperatingSystemCommandParameter::CurrentDirectoryParameter(
+                        String::from(directory),
+                    ))
+                } else if let Some(variable) = rest.strip_prefix("SetUserVar=") {
+                    let (name, value) = variable.split_once('=')?;
+                    Some(OperatingSystemCommandParameter::SetUserVariableParameter(
+                        String::from(name),
+                        String::from_utf8(base64::decode(value)?).ok()?,
+                    ))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // Reads the first OSC found in a complete sequence such as a terminal reply, e.g.
+    // "\x1b]52;c;aGk=\x07".
+    pub fn parse_sequence(sequence: &str) -> Option<OperatingSystemCommandParameter> {
+        parser::parse(sequence)
+            .into_iter()
+            .find_map(|segment| match segment {
+                parser::Segment::OperatingSystemCommandSegment(payload) => {
+                    OperatingSystemCommandParameter::parse(&payload)
+                }
+                _ => None,
+            })
+    }
+}
+
+impl fmt::Display for OperatingSystemCommandParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            OperatingSystemCommandParameter::HyperlinkStartParameter(hyperlink) => {
+                let uri = percent_encode(&hyperlink.uri, is_uri_byte);
+                match &hyperlink.id {
+                    Some(id) => format!("8;id={};{}", percent_encode(id, is_id_byte), uri),
+                    None => format!("8;;{}", uri),
+                }
+            }
+            OperatingSystemCommandParameter::HyperlinkEndParameter => String::from("8;;"),
+            OperatingSystemCommandParameter::SetIconNameAndWindowTitleParameter(title) => {
+                format!("0;{}", strip_control_characters(title))
+            }
+            OperatingSystemCommandParameter::SetIconNameParameter(icon_name) => {
+                format!("1;{}", strip_control_characters(icon_name))
+            }
+            OperatingSystemCommandParameter::SetWindowTitleParameter(title) => {
+                format!("2;{}", strip_control_characters(title))
+            }
+            OperatingSystemCommandParameter::CurrentWorkingDirectoryParameter(host, path) => {
+                format!(
+                    "7;file://{}{}",
+                    percent_encode(host, is_unreserved_byte),
+                    percent_encode(path, is_path_byte)
+                )
+            }
+            OperatingSystemCommandParameter::SetClipboardParameter(targets, text) => {
+                format!(
+                    "52;{};{}",
+                    selection_targets_to_string(targets),
+                    base64::encode(text.as_bytes())
+                )
+            }
+            OperatingSystemCommandParameter::QueryClipboardParameter(targets) => {
+                format!("52;{};?", selection_targets_to_string(targets))
+            }
+            OperatingSystemCommandParameter::SetPaletteColorParameter(color_code, color) => {
+                format!("4;{};{}", color_code, color_spec(*color))
+            }
+            OperatingSystemCommandParameter::QueryPaletteColorParameter(color_code) => {
+                format!("4;{};?", color_code)
+            }
+            OperatingSystemCommandParameter::ResetPaletteColorParameter(color_code) => {
+                match color_code {
+                    Some(color_code) => format!("104;{}", color_code),
+                    None => String::from("104"),
+                }
+            }
+            OperatingSystemCommandParameter::SetDefaultForegroundColorParameter(color) => {
+                format!("10;{}", color_spec(*color))
+            }
+            OperatingSystemCommandParameter::QueryDefaultForegroundColorParameter => {
+                String::from("10;?")
+            }
+            OperatingSystemCommandParameter::ResetDefaultForegroundColorParameter => {
+                String::from("110")
+            }
+            OperatingSystemCommandParameter::SetDefaultBackgroundColorParameter(color) => {
+                format!("11;{}", color_spec(*color))
+            }
+            OperatingSystemCommandParameter::QueryDefaultBackgroundColorParameter => {
+                String::from("11;?")
+            }
+            OperatingSystemCommandParameter::ResetDefaultBackgroundColorParameter => {
+                String::from("111")
+            }
+            OperatingSystemCommandParameter::SetCursorColorParameter(color) => {
+                format!("12;{}", color_spec(*color))
+            }
+            OperatingSystemCommandParameter::QueryCursorColorParameter => String::from("12;?"),
+            OperatingSystemCommandParameter::ResetCursorColorParameter => String::from("112"),
+            OperatingSystemCommandParameter::InlineImageParameter(image) => {
+                format!("1337;{}", image)
+            }
+            OperatingSystemCommandParameter::SetMarkParameter => String::from("1337;SetMark"),
+            OperatingSystemCommandParameter::CurrentDirectoryParameter(directory) => {
+                format!("1337;CurrentDir={}", strip_control_characters(directory))
+            }
+            OperatingSystemCommandParameter::SetUserVariableParameter(name, value) => format!(
+                "1337;SetUserVar={}={}",
+                strip_control_characters(name),
+                base64::encode(value.as_bytes())
+            ),
+        };
+        f.write_str(&text)
+    }
+}
+
+fn color_spec(color: Color) -> String {
+    let (red_color_code, green_color_code, blue_color_code) = color.to_rgb();
+    format!(
+        "rgb:{:02x}/{:02x}/{:02x}",
+        red_color_code.min(255),
+        green_color_code.min(255),
+        blue_color_code.min(255)
+    )
+}
+
+// Reads an X11 color specification as sent in replies to color queries, either
+// "rgb:r/g/b" with 1 to 4 hex digits per channel or the legacy "#rgb" forms, scaled to
+// 8 bits per channel.
+pub fn parse_color_spec(spec: &str) -> Option<Color> {
+    let channels: Vec<u32> = if let Some(rgb) = spec.strip_prefix("rgb:") {
+        let channels = rgb
+            .split('/')
+            .map(|channel| {
+                if channel.is_empty() || channel.len() > 4 || !is_hex(channel) {
+                    return None;
+                }
+                let value = u32::from_str_radix(channel, 16).ok()?;
+                let max = (1 << (4 * channel.len())) - 1;
+                Some((value * 255 + max / 2) / max)
+            })
+            .collect::<Option<Vec<u32>>>()?;
+        channels
+    } else if let Some(hex) = spec.strip_prefix('#') {
+        let width = hex.len() / 3;
+        if hex.len() % 3 != 0 || !(1..=4).contains(&width) || !is_hex(hex) {
+            return None;
+        }
+        // In the legacy form the digits are the high bits of each channel.
+        (0..3)
+            .map(|index| {
+                let value =
+                    u32::from_str_radix(&hex[index * width..(index + 1) * width], 16).ok()?;
+                Some(if width == 1 {
+                    value << 4
+                } else {
+                    value >> (4 * (width - 2))
+                })
+            })
+            .collect::<Option<Vec<u32>>>()?
+    } else {
+        return None;
+    };
+    match channels.as_slice() {
+        [red_color_code, green_color_code, blue_color_code] => Some(Color::RGB(
+            *red_color_code,
+            *green_color_code,
+            *blue_color_code,
+        )),
+        _ => None,
+    }
+}
+
+// from_str_radix alone would also accept a leading sign.
+fn is_hex(digits: &str) -> bool {
+    digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+fn selection_targets_to_string(targets: &[SelectionTarget]) -> String {
+    targets.iter().map(SelectionTarget::to_string).collect()
+}
+
+// A title containing ESC or BEL would end the sequence early and leak the rest to the screen.
+fn strip_control_characters(value: &str) -> String {
+    value.chars().filter(|c| !c.is_control()).collect()
+}
+
+// OSC 8 only allows printable ASCII in the URI.
+fn is_uri_byte(byte: u8) -> bool {
+    (0x21..=0x7e).contains(&byte)
+}
+
+// Colons separate the link's parameters and semicolons end them.
+fn is_id_byte(byte: u8) -> bool {
+    is_uri_byte(byte) && !b":;%".contains(&byte)
+}
+
+fn is_unreserved_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~".contains(&byte)
+}
+
+fn is_path_byte(byte: u8) -> bool {
+    is_unreserved_byte(byte) || byte == b'/'
+}
+
+pub(crate) fn percent_encode(value: &str, is_allowed: fn(u8) -> bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for &byte in value.as_bytes() {
+        if is_allowed(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let high = (bytes[index + 1] as char).to_digit(16);
+            let low = (bytes[index + 2] as char).to_digit(16);
+            if let (Some(high), Some(low)) = (high, low) {
+                decoded.push((high * 16 + low) as u8);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,237 @@
+use crate::{
+    ASCIIControlCode, ApplicationProgramCommandPart, ControlSequencePart, DeviceControlStringPart,
+    OperatingSystemCommandPart,
+};
+use std::fmt;
+
+// A control sequence as read from input: ESC [ parameters intermediates final.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControlSequence {
+    pub parameters: String,
+    pub intermediates: String,
+    pub final_byte: char,
+}
+
+impl fmt::Display for ControlSequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}",
+            ControlSequencePart::ControlSequenceInducer,
+            self.parameters,
+            self.intermediates,
+            self.final_byte
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    TextSegment(String),
+    ControlCodeSegment(char),
+    ControlSequenceSegment(ControlSequence),
+    OperatingSystemCommandSegment(String),
+    DeviceControlStringSegment(String),
+    ApplicationProgramCommandSegment(String),
+    StartOfStringSegment(String),
+    PrivacyMessageSegment(String),
+    EscapeSegment(String),
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let escape = ASCIIControlCode::EscapeCode;
+        let terminator = OperatingSystemCommandPart::StringTerminator;
+        match self {
+            Segment::TextSegment(text) => f.write_str(text),
+            Segment::ControlCodeSegment(code) => write!(f, "{}", code),
+            Segment::ControlSequenceSegment(sequence) => write!(f, "{}", sequence),
+            Segment::OperatingSystemCommandSegment(payload) => write!(
+                f,
+                "{}{}{}",
+                OperatingSystemCommandPart::OperatingSystemCommandIntroducer,
+                payload,
+                terminator
+            ),
+            Segment::DeviceControlStringSegment(payload) => write!(
+                f,
+                "{}{}{}",
+                DeviceControlStringPart::DeviceControlStringIntroducer,
+                payload,
+                terminator
+            ),
+            Segment::ApplicationProgramCommandSegment(payload) => write!(
+                f,
+                "{}{}{}",
+                ApplicationProgramCommandPart::ApplicationProgramCommandIntroducer,
+                payload,
+                terminator
+            ),
+            Segment::StartOfStringSegment(payload) => {
+                write!(f, "{}X{}{}", escape, payload, terminator)
+            }
+            Segment::PrivacyMessageSegment(payload) => {
+                write!(f, "{}^{}{}", escape, payload, terminator)
+            }
+            Segment::EscapeSegment(sequence) => write!(f, "{}{}", escape, sequence),
+        }
+    }
+}
+
+enum ControlStringKind {
+    OperatingSystemCommand,
+    DeviceControlString,
+    ApplicationProgramCommand,
+    StartOfString,
+    PrivacyMessage,
+}
+
+impl ControlStringKind {
+    fn into_segment(self, payload: String) -> Segment {
+        match self {
+            ControlStringKind::OperatingSystemCommand => {
+                Segment::OperatingSystemCommandSegment(payload)
+            }
+            ControlStringKind::DeviceControlString => Segment::DeviceControlStringSegment(payload),
+            ControlStringKind::ApplicationProgramCommand => {
+                Segment::ApplicationProgramCommandSegment(payload)
+            }
+            ControlStringKind::StartOfString => Segment::StartOfStringSegment(payload),
+            ControlStringKind::PrivacyMessage => Segment::PrivacyMessageSegment(payload),
+        }
+    }
+}
+
+// Splits input into plain text and the escape sequences embedded in it. Both the 7-bit
+// (ESC-prefixed) and 8-bit C1 forms of the introducers are recognised. A sequence that is
+// malformed or cut off by the end of the input is reported as a bare ESC control code
+// followed by whatever came after it.
+pub fn parse(input: &str) -> Vec<Segment> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let parsed = match c {
+            '\x1b' => parse_escape(&chars, index + 1),
+            '\u{9b}' => parse_control_sequence(&chars, index + 1),
+            '\u{9d}' => {
+                parse_control_string(&chars, index + 1, ControlStringKind::OperatingSystemCommand)
+            }
+            '\u{90}' => {
+                parse_control_string(&chars, index + 1, ControlStringKind::DeviceControlString)
+            }
+            '\u{9f}' => parse_control_string(
+                &chars,
+                index + 1,
+                ControlStringKind::ApplicationProgramCommand,
+            ),
+            '\u{98}' => parse_control_string(&chars, index + 1, ControlStringKind::StartOfString),
+            '\u{9e}' => parse_control_string(&chars, index + 1, ControlStringKind::PrivacyMessage),
+            _ => None,
+        };
+        match parsed {
+            Some((segment, next_index)) => {
+                if !text.is_empty() {
+                    segments.push(Segment::TextSegment(std::mem::take(&mut text)));
+                }
+                segments.push(segment);
+                index = next_index;
+            }
+            None if c.is_control() => {
+                if !text.is_empty() {
+                    segments.push(Segment::TextSegment(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::ControlCodeSegment(c));
+                index += 1;
+            }
+            None => {
+                text.push(c);
+                index += 1;
+            }
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::TextSegment(text));
+    }
+    segments
+}
+
+fn parse_escape(chars: &[char], index: usize) -> Option<(Segment, usize)> {
+    match chars.get(index)? {
+        '[' => parse_control_sequence(chars, index + 1),
+        ']' => parse_control_string(chars, index + 1, ControlStringKind::OperatingSystemCommand),
+        'P' => parse_control_string(chars, index + 1, ControlStringKind::DeviceControlString),
+        '_' => parse_control_string(
+            chars,
+            index + 1,
+            ControlStringKind::ApplicationProgramCommand,
+        ),
+        'X' => parse_control_string(chars, index + 1, ControlStringKind::StartOfString),
+        '^' => parse_control_string(chars, index + 1, ControlStringKind::PrivacyMessage),
+        _ => {
+            let mut end = index;
+            while end < chars.len() && ('\x20'..='\x2f').contains(&chars[end]) {
+                end += 1;
+            }
+            match chars.get(end) {
+                Some(&c) if ('\x30'..='\x7e').contains(&c) => {
+                    let sequence: String = chars[index..=end].iter().collect();
+                    Some((Segment::EscapeSegment(sequence), end + 1))
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+fn parse_control_sequence(chars: &[char], index: usize) -> Option<(Segment, usize)> {
+    let mut end = index;
+    while end < chars.len() && ('\x30'..='\x3f').contains(&chars[end]) {
+        end += 1;
+    }
+    let parameters: String = chars[index..end].iter().collect();
+    let intermediates_start = end;
+    while end < chars.len() && ('\x20'..='\x2f').contains(&chars[end]) {
+        end += 1;
+    }
+    let intermediates: String = chars[intermediates_start..end].iter().collect();
+    match chars.get(end) {
+        Some(&final_byte) if ('\x40'..='\x7e').contains(&final_byte) => Some((
+            Segment::ControlSequenceSegment(ControlSequence {
+                parameters,
+                intermediates,
+                final_byte,
+            }),
+            end + 1,
+        )),
+        _ => None,
+    }
+}
+
+// Control strings end at ST (ESC \ or its C1 form). BEL is also accepted since xterm
+// allows it as the terminator of an OSC and many programs rely on that.
+fn parse_control_string(
+    chars: &[char],
+    index: usize,
+    kind: ControlStringKind,
+) -> Option<(Segment, usize)> {
+    let mut end = index;
+    while end < chars.len() {
+        match chars[end] {
+            '\x07' | '\u{9c}' => {
+                let payload: String = chars[index..end].iter().collect();
+                return Some((kind.into_segment(payload), end + 1));
+            }
+            '\x1b' if chars.get(end + 1) == Some(&'\\') => {
+                let payload: String = chars[index..end].iter().collect();
+                return Some((kind.into_segment(payload), end + 2));
+            }
+            '\x1b' => return None,
+            _ => end += 1,
+        }
+    }
+    None
+}
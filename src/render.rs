@@ -0,0 +1,319 @@
+use crate::screen::WIDE_CONTINUATION;
+use crate::{width, Cell, EscapeSequence, Style};
+
+// Draws full-screen frames by comparing the frame being built with the one the terminal shows
+// and writing only the cells that differ, using whichever cursor movement is shortest and
+// changing only the attributes that differ between one cell's style and the next.
+//
+// The renderer assumes nothing else writes to the terminal between renders. It does not know
+// what is on the terminal before the first render, so that one clears the screen and draws
+// everything; invalidate asks for the same after something else has drawn.
+pub struct DiffRenderer {
+    width: usize,
+    height: usize,
+    previous: Vec<Cell>, // what the terminal shows
+    next: Vec<Cell>,
+    // Where the terminal's cursor is, or None when unknown or waiting to wrap.
+    cursor: Option<(usize, usize)>,
+    pen: Style,
+    final_cursor: Option<(usize, usize)>,
+    full_redraw: bool,
+    synchronized_output: bool,
+    repeat: bool,
+}
+
+impl DiffRenderer {
+    pub fn new(width: usize, height: usize) -> DiffRenderer {
+        let width = width.max(1);
+        let height = height.max(1);
+        DiffRenderer {
+            width,
+            height,
+            previous: vec![blank(); width * height],
+            next: vec![blank(); width * height],
+            cursor: None,
+            pen: Style::new(),
+            final_cursor: None,
+            full_redraw: true,
+            synchronized_output: false,
+            repeat: true,
+        }
+    }
+
+    // Wraps each update in mode 2026 so terminals that support it show the frame all at once.
+    // Other terminals ignore the mode.
+    pub fn with_synchronized_output(mut self, enabled: bool) -> DiffRenderer {
+        self.synchronized_output = enabled;
+        self
+    }
+
+    // REP shortens runs of the same character but is missing from some terminals, such as
+    // the Linux console.
+    pub fn with_repeat(mut self, enabled: bool) -> DiffRenderer {
+        self.repeat = enabled;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // A cell of the frame being built.
+    pub fn cell(&self, row: usize, column: usize) -> Option<Cell> {
+        if row < self.height && column < self.width {
+            Some(self.next[row * self.width + column])
+        } else {
+            None
+        }
+    }
+
+    // Wide characters take up the next column as well. Zero width characters and cells
+    // outside the frame are ignored.
+    pub fn set_cell(&mut self, row: usize, column: usize, cell: Cell) {
+        let character_width = width::char_width(cell.character);
+        if row >= self.height || column >= self.width || character_width == 0 {
+            return;
+        }
+        let index = row * self.width + column;
+        self.clear_wide_character(index);
+        if character_width == 2 && column + 1 < self.width {
+            self.clear_wide_character(index + 1);
+            self.next[index] = cell;
+            self.next[index + 1] = Cell {
+                character: WIDE_CONTINUATION,
+                style: cell.style,
+            };
+        } else if character_width == 2 {
+            self.next[index] = Cell {
+                character: ' ',
+                style: cell.style,
+            };
+        } else {
+            self.next[index] = cell;
+        }
+    }
+
+    // Writes text into the frame from the given position without wrapping, and returns the
+    // column after the last character written. Control codes and zero width characters are
+    // skipped.
+    pub fn print(&mut self, row: usize, column: usize, text: &str, style: Style) -> usize {
+        let mut column = column;
+        for character in text.chars() {
+            let character_width = width::char_width(character);
+            if character_width == 0 || character.is_control() {
+                continue;
+            }
+            if column >= self.width {
+                break;
+            }
+            self.set_cell(row, column, Cell { character, style });
+            column += character_width;
+        }
+        column.min(self.width)
+    }
+
+    // Blanks the frame being built; what the terminal shows is unchanged until the next render.
+    pub fn clear(&mut self) {
+        for cell in &mut self.next {
+            *cell = blank();
+        }
+    }
+
+    // Where to leave the cursor after each render, as a zero-based row and column. By default
+    // it is left after the last cell drawn.
+    pub fn set_cursor(&mut self, position: Option<(usize, usize)>) {
+        self.final_cursor =
+            position.map(|(row, column)| (row.min(self.height - 1), column.min(self.width - 1)));
+    }
+
+    // Keeps what fits of the frame being built; the next render redraws everything.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let width = width.max(1);
+        let height = height.max(1);
+        let mut next = vec![blank(); width * height];
+        for row in 0..self.height.min(height) {
+            for column in 0..self.width.min(width) {
+                next[row * width + column] = self.next[row * self.width + column];
+            }
+            // A wide character cut in half by the new right edge.
+            let last = row * width + width - 1;
+            if width < self.width && width::char_width(next[last].character) == 2 {
+                next[last].character = ' ';
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.next = next;
+        self.previous = vec![blank(); width * height];
+        self.final_cursor = self
+            .final_cursor
+            .map(|(row, column)| (row.min(height - 1), column.min(width - 1)));
+        self.invalidate();
+    }
+
+    pub fn invalidate(&mut self) {
+        self.full_redraw = true;
+    }
+
+    // The output that turns what the terminal shows into the frame being built. The frame is
+    // kept, so the next one can be built by changing only some of its cells.
+    pub fn render(&mut self) -> String {
+        let mut output = String::new();
+        if self.full_redraw {
+            output.push_str(&Style::new().to_string());
+            output.push_str(&EscapeSequence::CursorPositionSequence(1, 1).to_string());
+            output.push_str(&EscapeSequence::EraseScreenSequence.to_string());
+            self.previous = vec![blank(); self.width * self.height];
+            self.pen = Style::new();
+            self.cursor = Some((0, 0));
+            self.full_redraw = false;
+        }
+        for row in 0..self.height {
+            let mut column = 0;
+            while column < self.width {
+                let index = row * self.width + column;
+                let cell = self.next[index];
+                if cell == self.previous[index] || cell.character == WIDE_CONTINUATION {
+                    column += 1;
+                    continue;
+                }
+                self.move_to(row, column, &mut output);
+                output.push_str(&cell.style.transition_from(self.pen));
+                self.pen = cell.style;
+                output.push(cell.character);
+                let mut covered = if width::char_width(cell.character) == 2 {
+                    2
+                } else {
+                    1
+                };
+                if self.repeat && covered == 1 {
+                    let run = self.next[index..row * self.width + self.width]
+                        .iter()
+                        .take_while(|&&other| other == cell)
+                        .count();
+                    let repeat = EscapeSequence::RepeatSequence(run as u32 - 1).to_string();
+                    if run > 1 && repeat.len() < (run - 1) * cell.character.len_utf8() {
+                        output.push_str(&repeat);
+                        covered = run;
+                    }
+                }
+                self.previous[index..index + covered]
+                    .copy_from_slice(&self.next[index..index + covered]);
+                column += covered;
+                // After the last column the cursor waits to wrap, which terminals handle
+                // differently, so only an absolute move is trusted from there.
+                self.cursor = if column < self.width {
+                    Some((row, column))
+                } else {
+                    None
+                };
+            }
+        }
+        if let Some((row, column)) = self.final_cursor {
+            self.move_to(row, column, &mut output);
+        }
+        if output.is_empty() {
+            return output;
+        }
+        if self.synchronized_output {
+            output.insert_str(
+                0,
+                &EscapeSequence::BeginSynchronizedUpdateSequence.to_string(),
+            );
+            output.push_str(&EscapeSequence::EndSynchronizedUpdateSequence.to_string());
+        }
+        output
+    }
+
+    // Moves the cursor with the shortest of an absolute position, relative moves, carriage
+    // returns and line feeds, or rewriting the unchanged cells in between.
+    fn move_to(&mut self, row: usize, column: usize, output: &mut String) {
+        if self.cursor == Some((row, column)) {
+            return;
+        }
+        let mut best =
+            EscapeSequence::CursorPositionSequence(row as u32 + 1, column as u32 + 1).to_string();
+        if let Some((cursor_row, cursor_column)) = self.cursor {
+            let mut vertical = Vec::new();
+            if row == cursor_row {
+                vertical.push((String::new(), cursor_column));
+            } else if row > cursor_row {
+                let count = (row - cursor_row) as u32;
+                vertical.push((
+                    EscapeSequence::CursorDownSequence(count).to_string(),
+                    cursor_column,
+                ));
+                vertical.push(("\r\n".repeat(row - cursor_row), 0));
+            } else {
+                let count = (cursor_row - row) as u32;
+                vertical.push((
+                    EscapeSequence::CursorUpSequence(count).to_string(),
+                    cursor_column,
+                ));
+            }
+            for (vertical, from) in vertical {
+                let horizontal = self.horizontal_move(row, from, column);
+                if vertical.len() + horizontal.len() < best.len() {
+                    best = vertical + &horizontal;
+                }
+            }
+        }
+        output.push_str(&best);
+        self.cursor = Some((row, column));
+    }
+
+    fn horizontal_move(&self, row: usize, from: usize, to: usize) -> String {
+        if from == to {
+            return String::new();
+        }
+        let mut moves = vec![
+            EscapeSequence::CursorHorizontalAbsoluteSequence(to as u32 + 1).to_string(),
+            if to == 0 {
+                String::from("\r")
+            } else {
+                format!("\r{}", EscapeSequence::CursorForwardSequence(to as u32))
+            },
+        ];
+        if to > from {
+            moves.push(EscapeSequence::CursorForwardSequence((to - from) as u32).to_string());
+            // Unchanged cells already in the current style can simply be written again.
+            let start = row * self.width;
+            let cells = &self.previous[start + from..start + to];
+            let rewritable = cells
+                .iter()
+                .all(|cell| cell.style == self.pen && width::char_width(cell.character) == 1)
+                && self.next[start + from..start + to] == *cells;
+            if rewritable {
+                moves.push(cells.iter().map(|cell| cell.character).collect());
+            }
+        } else {
+            moves.push(EscapeSequence::CursorBackSequence((from - to) as u32).to_string());
+        }
+        moves
+            .into_iter()
+            .min_by_key(|candidate| candidate.len())
+            .unwrap_or_default()
+    }
+
+    // Overwriting either half of a wide character leaves the other half blank.
+    fn clear_wide_character(&mut self, index: usize) {
+        if self.next[index].character == WIDE_CONTINUATION {
+            self.next[index - 1].character = ' ';
+            self.next[index].character = ' ';
+        } else if width::char_width(self.next[index].character) == 2 {
+            self.next[index].character = ' ';
+            self.next[index + 1].character = ' ';
+        }
+    }
+}
+
+fn blank() -> Cell {
+    Cell {
+        character: ' ',
+        style: Style::new(),
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{parser, OperatingSystemCommandParameter};
+
+// Sequences a policy can let through. Anything outside these categories, such as device
+// queries, mode changes, palette changes, DCS, APC and other control strings, is never
+// passed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceCategory {
+    GraphicRenditionCategory, // SGR
+    HyperlinkCategory,        // OSC 8
+    CursorMovementCategory,   // relative and absolute positioning, save and restore
+    EraseCategory,            // erase in display or line, erase character
+    WindowTitleCategory,      // OSC 0, 1 and 2
+    ClipboardCategory,        // OSC 52, which can read the clipboard as well as write it
+}
+
+// How a control code or sequence that is not allowed is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplacementNotation {
+    DropNotation,           // removed without a trace
+    CaretNotation,          // ^[[2J
+    ControlPictureNotation, // ␛[2J
+}
+
+pub struct SanitizePolicy {
+    allowed_categories: Vec<SequenceCategory>,
+    allowed_control_codes: Vec<char>,
+    notation: ReplacementNotation,
+}
+
+impl SanitizePolicy {
+    // Plain text only: newlines and tabs are kept and every other control code and sequence
+    // is dropped.
+    pub fn new() -> SanitizePolicy {
+        SanitizePolicy {
+            allowed_categories: Vec::new(),
+            allowed_control_codes: vec!['\n', '\t'],
+            notation: ReplacementNotation::DropNotation,
+        }
+    }
+
+    // Colors, text attributes and links, which cannot move the cursor or change the terminal.
+    pub fn styled_text() -> SanitizePolicy {
+        SanitizePolicy::new()
+            .with_category(SequenceCategory::GraphicRenditionCategory)
+            .with_category(SequenceCategory::HyperlinkCategory)
+    }
+
+    pub fn with_category(mut self, category: SequenceCategory) -> SanitizePolicy {
+        if !self.allowed_categories.contains(&category) {
+            self.allowed_categories.push(category);
+        }
+        self
+    }
+
+    // Carriage return is left out by default since it lets a line overwrite what came before.
+    pub fn with_control_code(mut self, code: char) -> SanitizePolicy {
+        if code.is_control() && code != '\x1b' && !self.allowed_control_codes.contains(&code) {
+            self.allowed_control_codes.push(code);
+        }
+        self
+    }
+
+    pub fn with_notation(mut self, notation: ReplacementNotation) -> SanitizePolicy {
+        self.notation = notation;
+        self
+    }
+
+    // Allowed sequences are re-emitted in their 7-bit form, so C1 introducers never get
+    // through even when the sequence itself does.
+    pub fn sanitize(&self, input: &str) -> String {
+        let mut output = String::new();
+        for segment in parser::parse(input) {
+            match segment {
+                parser::Segment::TextSegment(text) => output.push_str(&text),
+                parser::Segment::ControlCodeSegment(code) => {
+                    if self.allowed_control_codes.contains(&code) {
+                        output.push(code);
+                    } else {
+                        output.push_str(&self.replace(&code.to_string()));
+                    }
+                }
+                segment => {
+                    let allowed = category(&segment)
+                        .is_some_and(|category| self.allowed_categories.contains(&category));
+                    if allowed {
+                        output.push_str(&segment.to_string());
+                    } else {
+                        output.push_str(&self.replace(&segment.to_string()));
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    fn replace(&self, raw: &str) -> String {
+        match self.notation {
+            ReplacementNotation::DropNotation => String::new(),
+            ReplacementNotation::CaretNotation => raw.chars().map(caret).collect(),
+            ReplacementNotation::ControlPictureNotation => {
+                raw.chars().map(control_picture).collect()
+            }
+        }
+    }
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> SanitizePolicy {
+        SanitizePolicy::new()
+    }
+}
+
+fn category(segment: &parser::Segment) -> Option<SequenceCategory> {
+    match segment {
+        parser::Segment::ControlSequenceSegment(sequence) => {
+            // Private parameters (such as "?25l" or ">4;2m") select different functions.
+            let private = sequence
+                .parameters
+                .starts_with(|c| ('<'..='?').contains(&c));
+            if private || !sequence.intermediates.is_empty() {
+                return None;
+            }
+            match sequence.final_byte {
+                'm' => Some(SequenceCategory::GraphicRenditionCategory),
+                'A' | 'B' | 'C' | 'D' | 'E' | 'F' | 'G' | 'H' | 'f' | 'd' | 'e' | 'a' | '`'
+                | 'j' | 'k' | 's' | 'u' => Some(SequenceCategory::CursorMovementCategory),
+                'J' | 'K' | 'X' => Some(SequenceCategory::EraseCategory),
+                _ => None,
+            }
+        }
+        parser::Segment::EscapeSegment(sequence) => match sequence.as_str() {
+            "7" | "8" => Some(SequenceCategory::CursorMovementCategory),
+            _ => None,
+        },
+        // Terminals differ in which control codes end or abort a control string, so payloads
+        // containing any are never trusted.
+        parser::Segment::OperatingSystemCommandSegment(payload)
+            if !payload.chars().any(char::is_control) =>
+        {
+            match OperatingSystemCommandParameter::parse(payload)? {
+                OperatingSystemCommandParameter::HyperlinkStartParameter(_)
+                | OperatingSystemCommandParameter::HyperlinkEndParameter => {
+                    Some(SequenceCategory::HyperlinkCategory)
+                }
+                OperatingSystemCommandParameter::SetIconNameAndWindowTitleParameter(_)
+                | OperatingSystemCommandParameter::SetIconNameParameter(_)
+                | OperatingSystemCommandParameter::SetWindowTitleParameter(_) => {
+                    Some(SequenceCategory::WindowTitleCategory)
+                }
+                OperatingSystemCommandParameter::SetClipboardParameter(_, _)
+                | OperatingSystemCommandParameter::QueryClipboardParameter(_) => {
+                    Some(SequenceCategory::ClipboardCategory)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// ESC becomes ^[ and DEL ^?. C1 codes use the M- prefix of cat -v, so CSI becomes M-^[.
+fn caret(c: char) -> String {
+    match c as u32 {
+        code @ 0x00..=0x1f => format!("^{}", (code as u8 + 0x40) as char),
+        0x7f => String::from("^?"),
+        code @ 0x80..=0x9f => format!("M-^{}", (code as u8 - 0x40) as char),
+        _ => c.to_string(),
+    }
+}
+
+// The Control Pictures block only covers C0 and DEL; C1 codes fall back to caret notation.
+fn control_picture(c: char) -> String {
+    match c as u32 {
+        code @ 0x00..=0x1f => std::char::from_u32(0x2400 + code)
+            .unwrap_or('\u{fffd}')
+            .to_string(),
+        0x7f => String::from("\u{2421}"),
+        _ => caret(c),
+    }
+}
@@ -0,0 +1,418 @@
+use crate::{parser, width, Style};
+
+// Tab stops are assumed every eight columns.
+const TAB_WIDTH: usize = 8;
+// Enough zero width characters for any real grapheme cluster; further ones are dropped.
+const MAXIMUM_MARKS: usize = 16;
+
+// Stands in for the column covered by the right half of a wide character.
+pub(crate) const WIDE_CONTINUATION: char = '\0';
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub character: char,
+    pub style: Style,
+}
+
+impl Cell {
+    // Erased cells keep the current background, as most terminals do.
+    fn blank(style: Style) -> Cell {
+        Cell {
+            character: ' ',
+            style: Style {
+                background: style.background,
+                ..Style::new()
+            },
+        }
+    }
+}
+
+// A grid of cells that interprets written output the way a terminal would: text, line
+// control, cursor movement, erasure, insertion, deletion and repetition, scrolling margins
+// and SGR. Anything else is ignored. A sequence split across two calls to write is not recognised.
+pub struct Screen {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    marks: Vec<String>, // zero width characters attached to each cell
+    row: usize,
+    column: usize,
+    style: Style,
+    saved_cursor: (usize, usize, Style),
+    // Set after writing to the last column; the next character wraps to a new line first.
+    pending_wrap: bool,
+    last_character: Option<char>, // repeated by REP
+    top_margin: usize,
+    bottom_margin: usize,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Screen {
+        let width = width.max(1);
+        let height = height.max(1);
+        Screen {
+            width,
+            height,
+            cells: vec![Cell::blank(Style::new()); width * height],
+            marks: vec![String::new(); width * height],
+            row: 0,
+            column: 0,
+            style: Style::new(),
+            saved_cursor: (0, 0, Style::new()),
+            pending_wrap: false,
+            last_character: None,
+            top_margin: 0,
+            bottom_margin: height - 1,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Zero-based row and column.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    pub fn cell(&self, row: usize, column: usize) -> Option<Cell> {
+        if row < self.height && column < self.width {
+            Some(self.cells[row * self.width + column])
+        } else {
+            None
+        }
+    }
+
+    // The character of a cell followed by any zero width characters attached to it, such as
+    // combining marks. The right half of a wide character has no text of its own.
+    pub fn text(&self, row: usize, column: usize) -> Option<String> {
+        let cell = self.cell(row, column)?;
+        let mut text = String::new();
+        if cell.character != WIDE_CONTINUATION {
+            text.push(cell.character);
+            text.push_str(&self.marks[row * self.width + column]);
+        }
+        Some(text)
+    }
+
+    // The text of a row without its styles or trailing spaces.
+    pub fn row_text(&self, row: usize) -> String {
+        let text: String = (0..self.width)
+            .filter_map(|column| self.text(row, column))
+            .collect();
+        String::from(text.trim_end())
+    }
+
+    pub fn write(&mut self, input: &str) {
+        for segment in parser::parse(input) {
+            match segment {
+                parser::Segment::TextSegment(text) => {
+                    for c in text.chars() {
+                        self.print(c);
+                    }
+                }
+                parser::Segment::ControlCodeSegment(code) => self.control_code(code),
+                parser::Segment::ControlSequenceSegment(sequence) => {
+                    self.control_sequence(&sequence)
+                }
+                parser::Segment::EscapeSegment(sequence) => self.escape(&sequence),
+                _ => {}
+            }
+        }
+    }
+
+    // Wide characters take two cells, and move to the next line first when only one is left.
+    fn print(&mut self, c: char) {
+        let character_width = width::char_width(c);
+        if character_width == 0 {
+            self.attach(c);
+            return;
+        }
+        let wide = character_width == 2 && self.width > 1;
+        if self.pending_wrap || (wide && self.column + 1 == self.width) {
+            self.pending_wrap = false;
+            self.column = 0;
+            self.line_feed();
+        }
+        let index = self.row * self.width + self.column;
+        self.clear_wide_character(index);
+        self.cells[index] = Cell {
+            character: c,
+            style: self.style,
+        };
+        self.marks[index].clear();
+        if wide {
+            self.clear_wide_character(index + 1);
+            self.cells[index + 1] = Cell {
+                character: WIDE_CONTINUATION,
+                style: self.style,
+            };
+        }
+        self.last_character = Some(c);
+        let covered = if wide { 2 } else { 1 };
+        if self.column + covered == self.width {
+            self.pending_wrap = true;
+        } else {
+            self.column += covered;
+        }
+    }
+
+    // Zero width characters join the character before the cursor, or are dropped when there
+    // is none.
+    fn attach(&mut self, c: char) {
+        let column = if self.pending_wrap {
+            self.column
+        } else if self.column > 0 {
+            self.column - 1
+        } else {
+            return;
+        };
+        let mut index = self.row * self.width + column;
+        if self.cells[index].character == WIDE_CONTINUATION && column > 0 {
+            index -= 1;
+        }
+        if self.marks[index].chars().count() < MAXIMUM_MARKS {
+            self.marks[index].push(c);
+        }
+    }
+
+    fn control_code(&mut self, code: char) {
+        self.pending_wrap = false;
+        match code {
+            '\n' | '\x0b' | '\x0c' => self.line_feed(),
+            '\r' => self.column = 0,
+            '\x08' => self.column = self.column.saturating_sub(1),
+            '\t' => self.column = ((self.column / TAB_WIDTH + 1) * TAB_WIDTH).min(self.width - 1),
+            _ => {}
+        }
+    }
+
+    fn control_sequence(&mut self, sequence: &parser::ControlSequence) {
+        let private = sequence
+            .parameters
+            .starts_with(|c| ('<'..='?').contains(&c));
+        if private || !sequence.intermediates.is_empty() {
+            return;
+        }
+        if sequence.final_byte == 'm' {
+            self.style.apply(&sequence.parameters);
+            return;
+        }
+        if sequence.final_byte == 'b' {
+            if let Some(c) = self.last_character {
+                let count = sequence.parameters.parse().unwrap_or(1usize).max(1);
+                for _ in 0..count.min(self.width * self.height) {
+                    self.print(c);
+                }
+            }
+            return;
+        }
+        let parameters: Vec<usize> = sequence
+            .parameters
+            .split(';')
+            .map(|parameter| parameter.parse().unwrap_or(0))
+            .collect();
+        let raw = |index: usize| parameters.get(index).copied().unwrap_or(0);
+        // Movement counts and positions treat zero the same as the default of one.
+        let count = |index: usize| raw(index).max(1);
+        self.pending_wrap = false;
+        match sequence.final_byte {
+            'A' => self.row = self.row.saturating_sub(count(0)),
+            'B' | 'e' => self.row = self.row.saturating_add(count(0)).min(self.height - 1),
+            'C' | 'a' => self.column = self.column.saturating_add(count(0)).min(self.width - 1),
+            'D' => self.column = self.column.saturating_sub(count(0)),
+            'E' => {
+                self.row = self.row.saturating_add(count(0)).min(self.height - 1);
+                self.column = 0;
+            }
+            'F' => {
+                self.row = self.row.saturating_sub(count(0));
+                self.column = 0;
+            }
+            'G' | '`' => self.column = (count(0) - 1).min(self.width - 1),
+            'd' => self.row = (count(0) - 1).min(self.height - 1),
+            'H' | 'f' => {
+                self.row = (count(0) - 1).min(self.height - 1);
+                self.column = (count(1) - 1).min(self.width - 1);
+            }
+            'J' => {
+                let cursor = self.row * self.width + self.column;
+                match raw(0) {
+                    0 => self.erase(cursor, self.cells.len()),
+                    1 => self.erase(0, cursor + 1),
+                    _ => self.erase(0, self.cells.len()),
+                }
+            }
+            'K' => {
+                let start = self.row * self.width;
+                let cursor = start + self.column;
+                match raw(0) {
+                    0 => self.erase(cursor, start + self.width),
+                    1 => self.erase(start, cursor + 1),
+                    _ => self.erase(start, start + self.width),
+                }
+            }
+            'X' => {
+                let cursor = self.row * self.width + self.column;
+                let end = cursor
+                    .saturating_add(count(0))
+                    .min((self.row + 1) * self.width);
+                self.erase(cursor, end);
+            }
+            '@' => self.shift_characters(count(0), true),
+            'P' => self.shift_characters(count(0), false),
+            'L' | 'M' if self.row >= self.top_margin && self.row <= self.bottom_margin => {
+                let up = sequence.final_byte == 'M';
+                self.scroll(self.row, self.bottom_margin, count(0), up);
+                self.column = 0;
+            }
+            'S' => self.scroll(self.top_margin, self.bottom_margin, count(0), true),
+            'T' => self.scroll(self.top_margin, self.bottom_margin, count(0), false),
+            'r' => {
+                let top = count(0) - 1;
+                let bottom = if raw(1) == 0 {
+                    self.height - 1
+                } else {
+                    (raw(1) - 1).min(self.height - 1)
+                };
+                if top < bottom {
+                    self.top_margin = top;
+                    self.bottom_margin = bottom;
+                    self.row = 0;
+                    self.column = 0;
+                }
+            }
+            's' => self.save_cursor(),
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    fn escape(&mut self, sequence: &str) {
+        self.pending_wrap = false;
+        match sequence {
+            "7" => self.save_cursor(),
+            "8" => self.restore_cursor(),
+            "D" => self.line_feed(),
+            "E" => {
+                self.column = 0;
+                self.line_feed();
+            }
+            "M" => {
+                if self.row == self.top_margin {
+                    self.scroll(self.top_margin, self.bottom_margin, 1, false);
+                } else {
+                    self.row = self.row.saturating_sub(1);
+                }
+            }
+            "c" => *self = Screen::new(self.width, self.height),
+            _ => {}
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.row == self.bottom_margin {
+            self.scroll(self.top_margin, self.bottom_margin, 1, true);
+        } else if self.row + 1 < self.height {
+            self.row += 1;
+        }
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved_cursor = (self.row, self.column, self.style);
+    }
+
+    fn restore_cursor(&mut self) {
+        let (row, column, style) = self.saved_cursor;
+        self.row = row.min(self.height - 1);
+        self.column = column.min(self.width - 1);
+        self.style = style;
+    }
+
+    fn erase(&mut self, start: usize, end: usize) {
+        let blank = Cell::blank(self.style);
+        let end = end.min(self.cells.len());
+        if start >= end {
+            return;
+        }
+        for index in start..end {
+            self.cells[index] = blank;
+            self.marks[index].clear();
+        }
+        self.repair_row(start / self.width);
+        self.repair_row((end - 1) / self.width);
+    }
+
+    // Moves the rest of the line right (inserting blanks) or left (deleting characters).
+    fn shift_characters(&mut self, count: usize, insert: bool) {
+        let start = self.row * self.width + self.column;
+        let end = (self.row + 1) * self.width;
+        let count = count.min(end - start);
+        if insert {
+            self.cells[start..end].rotate_right(count);
+            self.marks[start..end].rotate_right(count);
+            self.erase(start, start + count);
+        } else {
+            self.cells[start..end].rotate_left(count);
+            self.marks[start..end].rotate_left(count);
+            self.erase(end - count, end);
+        }
+    }
+
+    // Scrolls the rows from top to bottom inclusive, blanking the rows that scroll in.
+    fn scroll(&mut self, top: usize, bottom: usize, count: usize, up: bool) {
+        let count = count.min(bottom + 1 - top);
+        let (start, end) = (top * self.width, (bottom + 1) * self.width);
+        let shift = count * self.width;
+        if up {
+            self.cells[start..end].rotate_left(shift);
+            self.marks[start..end].rotate_left(shift);
+            self.erase(end - shift, end);
+        } else {
+            self.cells[start..end].rotate_right(shift);
+            self.marks[start..end].rotate_right(shift);
+            self.erase(start, start + shift);
+        }
+    }
+
+    // Overwriting either half of a wide character leaves the other half blank.
+    fn clear_wide_character(&mut self, index: usize) {
+        let column = index % self.width;
+        if self.cells[index].character == WIDE_CONTINUATION && column > 0 {
+            self.blank_character(index - 1);
+            self.blank_character(index);
+        } else if width::char_width(self.cells[index].character) == 2 && column + 1 < self.width {
+            self.blank_character(index);
+            self.blank_character(index + 1);
+        }
+    }
+
+    // Blanks the halves of wide characters that erasing or shifting part of a row split apart.
+    fn repair_row(&mut self, row: usize) {
+        let start = row * self.width;
+        for column in 0..self.width {
+            let index = start + column;
+            let character = self.cells[index].character;
+            let orphaned = if character == WIDE_CONTINUATION {
+                column == 0 || width::char_width(self.cells[index - 1].character) != 2
+            } else {
+                width::char_width(character) == 2
+                    && self.width > 1
+                    && (column + 1 == self.width
+                        || self.cells[index + 1].character != WIDE_CONTINUATION)
+            };
+            if orphaned {
+                self.blank_character(index);
+            }
+        }
+    }
+
+    fn blank_character(&mut self, index: usize) {
+        self.cells[index].character = ' ';
+        self.marks[index].clear();
+    }
+}
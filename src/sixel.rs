@@ -0,0 +1,279 @@
+use crate::{BufferSizeError, DeviceControlStringPart};
+use std::collections::HashMap;
+
+// Images with more opaque pixels than this are sampled when building the palette.
+const MAXIMUM_PALETTE_SAMPLES: usize = 1 << 16;
+
+pub struct SixelEncoder {
+    palette_size: usize,
+    dithering: bool,
+}
+
+impl SixelEncoder {
+    pub fn new() -> SixelEncoder {
+        SixelEncoder {
+            palette_size: 256,
+            dithering: false,
+        }
+    }
+
+    // Sixel terminals offer at most 256 color registers, many only 16.
+    pub fn with_palette_size(mut self, palette_size: usize) -> SixelEncoder {
+        self.palette_size = palette_size.clamp(1, 256);
+        self
+    }
+
+    // Floyd-Steinberg error diffusion, which hides banding when the palette is small.
+    pub fn with_dithering(mut self, dithering: bool) -> SixelEncoder {
+        self.dithering = dithering;
+        self
+    }
+
+    // Takes `width * height` RGBA pixels in row order. Pixels with an alpha below one half are
+    // left transparent.
+    pub fn encode(
+        &self,
+        width: usize,
+        height: usize,
+        rgba: &[u8],
+    ) -> Result<String, BufferSizeError> {
+        BufferSizeError::check(rgba.len(), width, height, 4)?;
+        let palette = median_cut(opaque_pixels(width, height, rgba), self.palette_size);
+        let indices = self.quantize(width, height, rgba, &palette);
+
+        let mut sequence = format!(
+            "{}0;1;0q\"1;1;{};{}",
+            DeviceControlStringPart::DeviceControlStringIntroducer,
+            width,
+            height
+        );
+        for (index, [red, green, blue]) in palette.iter().enumerate() {
+            sequence.push_str(&format!(
+                "#{};2;{};{};{}",
+                index,
+                percent(*red),
+                percent(*green),
+                percent(*blue)
+            ));
+        }
+        let bands: Vec<String> = (0..height)
+            .step_by(6)
+            .map(|top| encode_band(width, height, top, &indices, palette.len()))
+            .collect();
+        sequence.push_str(&bands.join("-"));
+        sequence.push_str(&DeviceControlStringPart::StringTerminator.to_string());
+        Ok(sequence)
+    }
+
+    fn quantize(
+        &self,
+        width: usize,
+        height: usize,
+        rgba: &[u8],
+        palette: &[[u8; 3]],
+    ) -> Vec<Option<u8>> {
+        let mut nearest_cache: HashMap<[u8; 3], u8> = HashMap::new();
+        let mut nearest = |color: [u8; 3]| {
+            *nearest_cache
+                .entry(color)
+                .or_insert_with(|| nearest_color(palette, color))
+        };
+        let mut indices = vec![None; width * height];
+        if !self.dithering {
+            for (pixel, index) in indices.iter_mut().enumerate() {
+                let offset = pixel * 4;
+                if rgba[offset + 3] >= 128 {
+                    *index = Some(nearest([rgba[offset], rgba[offset + 1], rgba[offset + 2]]));
+                }
+            }
+            return indices;
+        }
+
+        let mut errors = vec![[0f32; 3]; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = y * width + x;
+                let offset = pixel * 4;
+                if rgba[offset + 3] < 128 {
+                    continue;
+                }
+                let mut color = [0u8; 3];
+                for channel in 0..3 {
+                    let value = rgba[offset + channel] as f32 + errors[pixel][channel];
+                    color[channel] = value.round().clamp(0.0, 255.0) as u8;
+                }
+                let index = nearest(color);
+                indices[pixel] = Some(index);
+                let chosen = palette[index as usize];
+                let error = [
+                    color[0] as f32 - chosen[0] as f32,
+                    color[1] as f32 - chosen[1] as f32,
+                    color[2] as f32 - chosen[2] as f32,
+                ];
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let neighbour_x = x as isize + dx;
+                    if neighbour_x < 0 || neighbour_x as usize >= width || y + dy >= height {
+                        return;
+                    }
+                    let neighbour = (y + dy) * width + neighbour_x as usize;
+                    for channel in 0..3 {
+                        errors[neighbour][channel] += error[channel] * weight;
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+        indices
+    }
+}
+
+impl Default for SixelEncoder {
+    fn default() -> SixelEncoder {
+        SixelEncoder::new()
+    }
+}
+
+fn opaque_pixels(width: usize, height: usize, rgba: &[u8]) -> Vec<[u8; 3]> {
+    let opaque: Vec<[u8; 3]> = rgba[..width * height * 4]
+        .chunks(4)
+        .filter(|pixel| pixel[3] >= 128)
+        .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect();
+    let step = opaque.len().div_ceil(MAXIMUM_PALETTE_SAMPLES).max(1);
+    opaque.into_iter().step_by(step).collect()
+}
+
+// Repeatedly splits the box of colors with the widest channel range at its median until the
+// palette is full or no box can be split any further.
+fn median_cut(colors: Vec<[u8; 3]>, palette_size: usize) -> Vec<[u8; 3]> {
+    if colors.is_empty() {
+        return Vec::new();
+    }
+    let mut boxes = vec![colors];
+    while boxes.len() < palette_size {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .map(|(index, colors)| (index, widest_channel(colors)))
+            .filter(|(_, (_, range))| *range > 0)
+            .max_by_key(|(_, (_, range))| *range);
+        let (index, (channel, _)) = match widest {
+            Some(widest) => widest,
+            None => break,
+        };
+        let mut colors = boxes.swap_remove(index);
+        colors.sort_unstable_by_key(|color| color[channel]);
+        // Split between distinct values so that no color ends up in both halves.
+        let median = colors[colors.len() / 2][channel];
+        let mut split = colors.partition_point(|color| color[channel] < median);
+        if split == 0 {
+            split = colors.partition_point(|color| color[channel] <= median);
+        }
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+    boxes.iter().map(|colors| average(colors)).collect()
+}
+
+fn widest_channel(colors: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let minimum = colors.iter().map(|color| color[channel]).min().unwrap_or(0);
+            let maximum = colors.iter().map(|color| color[channel]).max().unwrap_or(0);
+            (channel, maximum - minimum)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+fn average(colors: &[[u8; 3]]) -> [u8; 3] {
+    let mut sums = [0u64; 3];
+    for color in colors {
+        for channel in 0..3 {
+            sums[channel] += color[channel] as u64;
+        }
+    }
+    let count = colors.len().max(1) as u64;
+    [
+        (sums[0] / count) as u8,
+        (sums[1] / count) as u8,
+        (sums[2] / count) as u8,
+    ]
+}
+
+fn nearest_color(palette: &[[u8; 3]], color: [u8; 3]) -> u8 {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, candidate)| {
+            (0..3)
+                .map(|channel| {
+                    let difference = candidate[channel] as i32 - color[channel] as i32;
+                    difference * difference
+                })
+                .sum::<i32>()
+        })
+        .map(|(index, _)| index as u8)
+        .unwrap_or(0)
+}
+
+// Sixel color registers are given in percent.
+fn percent(value: u8) -> u32 {
+    (value as u32 * 100 + 127) / 255
+}
+
+// A band is six pixel rows. Each color used in it is drawn as one pass over the band, with `$`
+// returning to the start of the band between passes.
+fn encode_band(
+    width: usize,
+    height: usize,
+    top: usize,
+    indices: &[Option<u8>],
+    palette_size: usize,
+) -> String {
+    let mut masks = vec![0u8; palette_size * width];
+    let mut used = vec![false; palette_size];
+    for row in 0..6.min(height - top) {
+        for x in 0..width {
+            if let Some(index) = indices[(top + row) * width + x] {
+                masks[index as usize * width + x] |= 1 << row;
+                used[index as usize] = true;
+            }
+        }
+    }
+    let passes: Vec<String> = (0..palette_size)
+        .filter(|&index| used[index])
+        .map(|index| {
+            let row = &masks[index * width..(index + 1) * width];
+            let length = row.iter().rposition(|&mask| mask != 0).map_or(0, |x| x + 1);
+            format!("#{}{}", index, run_length_encode(&row[..length]))
+        })
+        .collect();
+    passes.join("$")
+}
+
+fn run_length_encode(masks: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut index = 0;
+    while index < masks.len() {
+        let mask = masks[index];
+        let mut run = 1;
+        while index + run < masks.len() && masks[index + run] == mask {
+            run += 1;
+        }
+        let sixel = (63 + mask) as char;
+        if run > 3 {
+            encoded.push_str(&format!("!{}{}", run, sixel));
+        } else {
+            for _ in 0..run {
+                encoded.push(sixel);
+            }
+        }
+        index += run;
+    }
+    encoded
+}
@@ -0,0 +1,368 @@
+use crate::{
+    background_parameter, foreground_parameter, Color, EscapeSequence,
+    SelectGraphicRenditionParameter,
+};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnderlineStyle {
+    NoUnderline,
+    SingleUnderline,
+    DoubleUnderline,
+    CurlyUnderline,
+    DottedUnderline,
+    DashedUnderline,
+}
+
+// The graphic rendition state a terminal keeps while writing text. System colors are stored
+// as the first sixteen 256-color codes, so 31 and 38;5;1 both give Color256(1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Style {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub underline_color: Option<Color>,
+    pub bold: bool,
+    pub faint: bool,
+    pub italic: bool,
+    pub underline: UnderlineStyle,
+    pub blink: bool,
+    pub reverse: bool,
+    pub conceal: bool,
+    pub crossed_out: bool,
+    pub overlined: bool,
+}
+
+impl Style {
+    pub fn new() -> Style {
+        Style {
+            foreground: None,
+            background: None,
+            underline_color: None,
+            bold: false,
+            faint: false,
+            italic: false,
+            underline: UnderlineStyle::NoUnderline,
+            blink: false,
+            reverse: false,
+            conceal: false,
+            crossed_out: false,
+            overlined: false,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Style::new()
+    }
+
+    // Applies the parameters of an SGR sequence such as "1;38;5;208" or "4:3". Colors may use
+    // either the semicolon or the colon form, and unknown codes are ignored.
+    pub fn apply(&mut self, parameters: &str) {
+        let parameters: Vec<&str> = parameters.split(';').collect();
+        let mut index = 0;
+        while index < parameters.len() {
+            let parameter = parameters[index];
+            index += 1;
+            if parameter.contains(':') {
+                self.apply_subparameters(parameter);
+                continue;
+            }
+            let code = match number(parameter) {
+                Some(code) => code,
+                None => continue,
+            };
+            match code {
+                38 | 48 | 58 => {
+                    let (color, used) = extended_color(&parameters[index..]);
+                    index += used;
+                    if let Some(color) = color {
+                        self.set_color(code, Some(color));
+                    }
+                }
+                code => self.apply_code(code),
+            }
+        }
+    }
+
+    fn apply_subparameters(&mut self, parameter: &str) {
+        let subparameters: Vec<&str> = parameter.split(':').collect();
+        match number(subparameters[0]) {
+            Some(4) => {
+                self.underline = match number(subparameters[1]) {
+                    Some(0) => UnderlineStyle::NoUnderline,
+                    Some(2) => UnderlineStyle::DoubleUnderline,
+                    Some(3) => UnderlineStyle::CurlyUnderline,
+                    Some(4) => UnderlineStyle::DottedUnderline,
+                    Some(5) => UnderlineStyle::DashedUnderline,
+                    _ => UnderlineStyle::SingleUnderline,
+                }
+            }
+            Some(code) if code == 38 || code == 48 || code == 58 => {
+                // The colon form of 38:2 may carry a color space id before the components.
+                let mut components = subparameters[1..].to_vec();
+                if components.len() > 4 && number(components[0]) == Some(2) {
+                    components.remove(1);
+                }
+                if let (Some(color), _) = extended_color(&components) {
+                    self.set_color(code, Some(color));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_code(&mut self, code: u32) {
+        match code {
+            0 => *self = Style::new(),
+            1 => self.bold = true,
+            2 => self.faint = true,
+            3 => self.italic = true,
+            4 => self.underline = UnderlineStyle::SingleUnderline,
+            5 | 6 => self.blink = true,
+            7 => self.reverse = true,
+            8 => self.conceal = true,
+            9 => self.crossed_out = true,
+            21 => self.underline = UnderlineStyle::DoubleUnderline,
+            22 => {
+                self.bold = false;
+                self.faint = false;
+            }
+            23 => self.italic = false,
+            24 => self.underline = UnderlineStyle::NoUnderline,
+            25 => self.blink = false,
+            27 => self.reverse = false,
+            28 => self.conceal = false,
+            29 => self.crossed_out = false,
+            30..=37 => self.foreground = Some(Color::Color256(code - 30)),
+            39 => self.foreground = None,
+            40..=47 => self.background = Some(Color::Color256(code - 40)),
+            49 => self.background = None,
+            53 => self.overlined = true,
+            55 => self.overlined = false,
+            59 => self.underline_color = None,
+            90..=97 => self.foreground = Some(Color::Color256(code - 90 + 8)),
+            100..=107 => self.background = Some(Color::Color256(code - 100 + 8)),
+            _ => {}
+        }
+    }
+
+    fn set_color(&mut self, code: u32, color: Option<Color>) {
+        match code {
+            38 => self.foreground = color,
+            48 => self.background = color,
+            _ => self.underline_color = color,
+        }
+    }
+
+    pub fn to_parameters(self) -> Vec<SelectGraphicRenditionParameter> {
+        let mut parameters = Vec::new();
+        if self.bold {
+            parameters.push(SelectGraphicRenditionParameter::BoldParameter);
+        }
+        if self.faint {
+            parameters.push(SelectGraphicRenditionParameter::FaintParameter);
+        }
+        if self.italic {
+            parameters.push(SelectGraphicRenditionParameter::ItalicParameter);
+        }
+        match self.underline {
+            UnderlineStyle::NoUnderline => {}
+            UnderlineStyle::SingleUnderline => {
+                parameters.push(SelectGraphicRenditionParameter::UnderlineParameter)
+            }
+            UnderlineStyle::DoubleUnderline => {
+                parameters.push(SelectGraphicRenditionParameter::DoubleUnderlineParameter)
+            }
+            UnderlineStyle::CurlyUnderline => {
+                parameters.push(SelectGraphicRenditionParameter::CurlyUnderlineParameter)
+            }
+            UnderlineStyle::DottedUnderline => {
+                parameters.push(SelectGraphicRenditionParameter::DottedUnderlineParameter)
+            }
+            UnderlineStyle::DashedUnderline => {
+                parameters.push(SelectGraphicRenditionParameter::DashedUnderlineParameter)
+            }
+        }
+        if self.blink {
+            parameters.push(SelectGraphicRenditionParameter::SlowBlinkParameter);
+        }
+        if self.reverse {
+            parameters.push(SelectGraphicRenditionParameter::ReverseVideoParameter);
+        }
+        if self.conceal {
+            parameters.push(SelectGraphicRenditionParameter::ConcealParameter);
+        }
+        if self.crossed_out {
+            parameters.push(SelectGraphicRenditionParameter::CrossedOutParameter);
+        }
+        if self.overlined {
+            parameters.push(SelectGraphicRenditionParameter::OverlinedParameter);
+        }
+        if let Some(color) = self.foreground {
+            parameters.push(foreground_parameter(color));
+        }
+        if let Some(color) = self.background {
+            parameters.push(background_parameter(color));
+        }
+        if let Some(color) = self.underline_color {
+            parameters.push(SelectGraphicRenditionParameter::SetUnderlineColorParameter(
+                color,
+            ));
+        }
+        parameters
+    }
+
+    // The shortest SGR sequence that turns the previous style into this one: either the
+    // attributes that changed, or a reset followed by the whole style. Empty when the two are
+    // the same.
+    pub fn transition_from(self, previous: Style) -> String {
+        if self == previous {
+            return String::new();
+        }
+        let mut parameters = Vec::new();
+        // Bold and faint are both turned off by 22.
+        if (previous.bold && !self.bold) || (previous.faint && !self.faint) {
+            parameters.push(SelectGraphicRenditionParameter::NormalIntensityParameter);
+            if self.bold {
+                parameters.push(SelectGraphicRenditionParameter::BoldParameter);
+            }
+            if self.faint {
+                parameters.push(SelectGraphicRenditionParameter::FaintParameter);
+            }
+        } else {
+            if self.bold && !previous.bold {
+                parameters.push(SelectGraphicRenditionParameter::BoldParameter);
+            }
+            if self.faint && !previous.faint {
+                parameters.push(SelectGraphicRenditionParameter::FaintParameter);
+            }
+        }
+        let toggles = [
+            (
+                previous.italic,
+                self.italic,
+                SelectGraphicRenditionParameter::ItalicParameter,
+                SelectGraphicRenditionParameter::NotItalicParameter,
+            ),
+            (
+                previous.blink,
+                self.blink,
+                SelectGraphicRenditionParameter::SlowBlinkParameter,
+                SelectGraphicRenditionParameter::BlinkOffParameter,
+            ),
+            (
+                previous.reverse,
+                self.reverse,
+                SelectGraphicRenditionParameter::ReverseVideoParameter,
+                SelectGraphicRenditionParameter::InverseOffParameter,
+            ),
+            (
+                previous.conceal,
+                self.conceal,
+                SelectGraphicRenditionParameter::ConcealParameter,
+                SelectGraphicRenditionParameter::RevealParameter,
+            ),
+            (
+                previous.crossed_out,
+                self.crossed_out,
+                SelectGraphicRenditionParameter::CrossedOutParameter,
+                SelectGraphicRenditionParameter::NotCrossedOutParameter,
+            ),
+            (
+                previous.overlined,
+                self.overlined,
+                SelectGraphicRenditionParameter::OverlinedParameter,
+                SelectGraphicRenditionParameter::NotOverlinedParameter,
+            ),
+        ];
+        for (was, is, on, off) in toggles {
+            if was != is {
+                parameters.push(if is { on } else { off });
+            }
+        }
+        if self.underline != previous.underline {
+            if self.underline == UnderlineStyle::NoUnderline {
+                parameters.push(SelectGraphicRenditionParameter::UnderlineOffParameter);
+            } else {
+                let underline = Style {
+                    underline: self.underline,
+                    ..Style::new()
+                };
+                parameters.extend(underline.to_parameters());
+            }
+        }
+        if self.foreground != previous.foreground {
+            parameters.push(match self.foreground {
+                Some(color) => foreground_parameter(color),
+                None => SelectGraphicRenditionParameter::DefaultForegroundColorParameter,
+            });
+        }
+        if self.background != previous.background {
+            parameters.push(match self.background {
+                Some(color) => background_parameter(color),
+                None => SelectGraphicRenditionParameter::DefaultBackgroundColorParameter,
+            });
+        }
+        if self.underline_color != previous.underline_color {
+            parameters.push(match self.underline_color {
+                Some(color) => SelectGraphicRenditionParameter::SetUnderlineColorParameter(color),
+                None => SelectGraphicRenditionParameter::DefaultUnderlineColorParameter,
+            });
+        }
+        let changes = EscapeSequence::SelectGraphicRenditionSequence(parameters).to_string();
+        let reset = self.to_string();
+        if reset.len() < changes.len() {
+            reset
+        } else {
+            changes
+        }
+    }
+}
+
+// An SGR sequence that resets first, so it gives this style whatever the previous one was.
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parameters = vec![SelectGraphicRenditionParameter::ResetNormalParameter];
+        parameters.extend(self.to_parameters());
+        write!(
+            f,
+            "{}",
+            EscapeSequence::SelectGraphicRenditionSequence(parameters)
+        )
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style::new()
+    }
+}
+
+// Empty parameters count as zero, as in the sequences terminals accept.
+fn number(parameter: &str) -> Option<u32> {
+    if parameter.is_empty() {
+        return Some(0);
+    }
+    parameter.parse().ok()
+}
+
+// Reads "5;n" or "2;r;g;b" following a 38, 48 or 58 and returns the color along with the
+// number of parameters it took up.
+fn extended_color(parameters: &[&str]) -> (Option<Color>, usize) {
+    let component = |index: usize| {
+        parameters
+            .get(index)
+            .and_then(|parameter| number(parameter))
+            .map(|value| value.min(255))
+    };
+    match parameters.first().and_then(|parameter| number(parameter)) {
+        Some(5) => match component(1) {
+            Some(color_code) => (Some(Color::Color256(color_code)), 2),
+            None => (None, parameters.len().min(2)),
+        },
+        Some(2) => match (component(1), component(2), component(3)) {
+            (Some(red), Some(green), Some(blue)) => (Some(Color::RGB(red, green, blue)), 4),
+            _ => (None, parameters.len().min(4)),
+        },
+        _ => (None, parameters.len().min(1)),
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::{parser, width, Style};
+
+// Text as runs of characters sharing one style. Empty spans are never kept and neighbouring
+// spans always differ in style, so two strings that look the same compare equal. Indices are
+// byte offsets into the unstyled text, as with String, and must fall on character boundaries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StyledString {
+    spans: Vec<(Style, String)>,
+}
+
+impl StyledString {
+    pub fn new() -> StyledString {
+        StyledString { spans: Vec::new() }
+    }
+
+    // Reads the text and SGR sequences of ANSI output. Newlines and tabs are kept as text;
+    // other control codes and sequences, links included, are dropped.
+    pub fn parse(input: &str) -> StyledString {
+        let mut styled = StyledString::new();
+        let mut style = Style::new();
+        for segment in parser::parse(input) {
+            match segment {
+                parser::Segment::TextSegment(text) => styled.push(style, &text),
+                parser::Segment::ControlCodeSegment(code @ '\n')
+                | parser::Segment::ControlCodeSegment(code @ '\t') => {
+                    styled.push(style, &code.to_string())
+                }
+                parser::Segment::ControlSequenceSegment(sequence)
+                    if sequence.final_byte == 'm' && sequence.intermediates.is_empty() =>
+                {
+                    style.apply(&sequence.parameters)
+                }
+                _ => {}
+            }
+        }
+        styled
+    }
+
+    pub fn spans(&self) -> &[(Style, String)] {
+        &self.spans
+    }
+
+    // The text without its styles.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|(_, text)| text.as_str()).collect()
+    }
+
+    // Length of the text in bytes.
+    pub fn len(&self) -> usize {
+        self.spans.iter().map(|(_, text)| text.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    pub fn width(&self) -> usize {
+        self.spans
+            .iter()
+            .map(|(_, text)| width::text_width(text))
+            .sum()
+    }
+
+    pub fn push(&mut self, style: Style, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some((last_style, last_text)) if *last_style == style => last_text.push_str(text),
+            _ => self.spans.push((style, String::from(text))),
+        }
+    }
+
+    pub fn concat(&mut self, other: StyledString) {
+        for (style, text) in other.spans {
+            self.push(style, &text);
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, style: Style, text: &str) {
+        self.replace_range(index..index, style, text);
+    }
+
+    // Replaces a range of the text with text in a single style. An empty replacement deletes
+    // the range.
+    pub fn replace_range(&mut self, range: Range<usize>, style: Style, text: &str) {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {:?} out of bounds for length {}",
+            range,
+            self.len()
+        );
+        let start = self.split_at(range.start);
+        let end = self.split_at(range.end);
+        self.spans
+            .splice(start..end, vec![(style, String::from(text))]);
+        let spans = std::mem::take(&mut self.spans);
+        for (style, text) in spans {
+            self.push(style, &text);
+        }
+    }
+
+    // Splits the span containing the byte index so that a span starts there, and returns the
+    // position of that span.
+    fn split_at(&mut self, index: usize) -> usize {
+        let mut offset = 0;
+        for position in 0..self.spans.len() {
+            let length = self.spans[position].1.len();
+            if index == offset {
+                return position;
+            }
+            if index < offset + length {
+                let (style, text) = &mut self.spans[position];
+                let rest = text.split_off(index - offset);
+                let style = *style;
+                self.spans.insert(position + 1, (style, rest));
+                return position + 1;
+            }
+            offset += length;
+        }
+        self.spans.len()
+    }
+}
+
+// The ANSI form, starting from and returning to the default style.
+impl fmt::Display for StyledString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut current = Style::new();
+        for (style, text) in &self.spans {
+            if *style != current {
+                write!(f, "{}", style)?;
+                current = *style;
+            }
+            f.write_str(text)?;
+        }
+        if !current.is_default() {
+            write!(f, "{}", Style::new())?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for StyledString {
+    fn default() -> StyledString {
+        StyledString::new()
+    }
+}
@@ -0,0 +1,252 @@
+use crate::{html, Cell, Color, Screen, Style, Theme, UnderlineStyle};
+
+// Colors of the close, minimize and zoom buttons drawn in the window chrome.
+const CHROME_BUTTONS: [&str; 3] = ["#ff5f57", "#febc2e", "#28c840"];
+
+// Draws a Screen as an SVG "terminal screenshot". Cells are laid out on a fixed grid, so the
+// font should be monospaced.
+pub struct SvgRenderer {
+    font_family: String,
+    font_size: f64,
+    line_height: f64,
+    theme: Theme,
+    chrome: bool,
+    title: String,
+    padding: f64,
+}
+
+impl SvgRenderer {
+    pub fn new() -> SvgRenderer {
+        SvgRenderer {
+            font_family: String::from(
+                "ui-monospace, Menlo, Consolas, 'DejaVu Sans Mono', monospace",
+            ),
+            font_size: 14.0,
+            line_height: 1.2,
+            theme: Theme::xterm(),
+            chrome: true,
+            title: String::new(),
+            padding: 16.0,
+        }
+    }
+
+    // The size is in pixels. Each cell is 0.6 of the size wide, the usual monospace advance.
+    pub fn with_font(mut self, font_family: &str, font_size: f64) -> SvgRenderer {
+        self.font_family = String::from(font_family);
+        self.font_size = font_size;
+        self
+    }
+
+    // Row height as a multiple of the font size.
+    pub fn with_line_height(mut self, line_height: f64) -> SvgRenderer {
+        self.line_height = line_height;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> SvgRenderer {
+        self.theme = theme;
+        self
+    }
+
+    // A title bar with window buttons above the terminal, and rounded corners.
+    pub fn with_chrome(mut self, chrome: bool) -> SvgRenderer {
+        self.chrome = chrome;
+        self
+    }
+
+    // Shown in the title bar when the chrome is drawn.
+    pub fn with_title(mut self, title: &str) -> SvgRenderer {
+        self.title = String::from(title);
+        self
+    }
+
+    pub fn with_padding(mut self, padding: f64) -> SvgRenderer {
+        self.padding = padding;
+        self
+    }
+
+    // Plays `output` on a fresh screen of the given size and draws the result.
+    pub fn render_output(&self, columns: usize, rows: usize, output: &str) -> String {
+        let mut screen = Screen::new(columns, rows);
+        screen.write(output);
+        self.render(&screen)
+    }
+
+    pub fn render(&self, screen: &Screen) -> String {
+        let cell_width = self.font_size * 0.6;
+        let cell_height = self.font_size * self.line_height;
+        let chrome_height = if self.chrome {
+            self.font_size * 2.5
+        } else {
+            0.0
+        };
+        let width = self.padding * 2.0 + screen.width() as f64 * cell_width;
+        let height = chrome_height + self.padding * 2.0 + screen.height() as f64 * cell_height;
+        let foreground = html::hex(self.theme.resolve(self.theme.foreground));
+        let background = html::hex(self.theme.resolve(self.theme.background));
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"{}\" font-size=\"{}\">\n",
+            number(width),
+            number(height),
+            number(width),
+            number(height),
+            html::escape(&self.font_family),
+            number(self.font_size)
+        );
+        svg.push_str(&format!(
+            "<rect width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\"/>\n",
+            number(width),
+            number(height),
+            if self.chrome { 8 } else { 0 },
+            background
+        ));
+        if self.chrome {
+            let radius = self.font_size * 0.45;
+            for (index, color) in CHROME_BUTTONS.iter().enumerate() {
+                svg.push_str(&format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                    number(self.padding + radius + index as f64 * radius * 3.2),
+                    number(chrome_height / 2.0),
+                    number(radius),
+                    color
+                ));
+            }
+            if !self.title.is_empty() {
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\" opacity=\"0.7\">{}</text>\n",
+                    number(width / 2.0),
+                    number(chrome_height / 2.0),
+                    foreground,
+                    html::escape(&self.title)
+                ));
+            }
+        }
+        svg.push_str(&format!(
+            "<g transform=\"translate({} {})\">\n",
+            number(self.padding),
+            number(chrome_height + self.padding)
+        ));
+        for row in 0..screen.height() {
+            let cells: Vec<_> = (0..screen.width())
+                .filter_map(|column| screen.cell(row, column))
+                .collect();
+            let y = row as f64 * cell_height;
+            for (start, length, style) in runs(&cells) {
+                let (_, run_background) = self.colors(style);
+                if let Some(run_background) = run_background {
+                    svg.push_str(&format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                        number(start as f64 * cell_width),
+                        number(y),
+                        number(length as f64 * cell_width),
+                        number(cell_height),
+                        run_background
+                    ));
+                }
+            }
+            // Text sits on a baseline centred in the row, with descenders below it.
+            let baseline = y + (cell_height - self.font_size) / 2.0 + self.font_size * 0.8;
+            for (start, length, style) in runs(&cells) {
+                let decorated = style.underline != UnderlineStyle::NoUnderline
+                    || style.crossed_out
+                    || style.overlined;
+                // Trailing blanks only need drawing when a line runs through them.
+                let length = if decorated {
+                    length
+                } else {
+                    cells[start..start + length]
+                        .iter()
+                        .rposition(|cell| cell.character != ' ')
+                        .map_or(0, |last| last + 1)
+                };
+                if style.conceal || length == 0 {
+                    continue;
+                }
+                // The right halves of wide characters add no text of their own.
+                let text: String = (start..start + length)
+                    .filter_map(|column| screen.text(row, column))
+                    .collect();
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\"{} xml:space=\"preserve\">{}</text>\n",
+                    number(start as f64 * cell_width),
+                    number(baseline),
+                    number(length as f64 * cell_width),
+                    self.text_attributes(style),
+                    html::escape(&text)
+                ));
+            }
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+
+    // Text color and, when it differs from the theme's, background color after reverse video.
+    fn colors(&self, style: Style) -> (String, Option<String>) {
+        let resolve = |color: Color| html::hex(self.theme.resolve(color));
+        if style.reverse {
+            (
+                resolve(style.background.unwrap_or(self.theme.background)),
+                Some(resolve(style.foreground.unwrap_or(self.theme.foreground))),
+            )
+        } else {
+            (
+                resolve(style.foreground.unwrap_or(self.theme.foreground)),
+                style.background.map(resolve),
+            )
+        }
+    }
+
+    fn text_attributes(&self, style: Style) -> String {
+        let (fill, _) = self.colors(style);
+        let mut attributes = format!(" fill=\"{}\"", fill);
+        if style.bold {
+            attributes.push_str(" font-weight=\"bold\"");
+        }
+        if style.faint {
+            attributes.push_str(" opacity=\"0.5\"");
+        }
+        if style.italic {
+            attributes.push_str(" font-style=\"italic\"");
+        }
+        let mut lines = Vec::new();
+        if style.underline != UnderlineStyle::NoUnderline {
+            lines.push("underline");
+        }
+        if style.crossed_out {
+            lines.push("line-through");
+        }
+        if style.overlined {
+            lines.push("overline");
+        }
+        if !lines.is_empty() {
+            attributes.push_str(&format!(" text-decoration=\"{}\"", lines.join(" ")));
+        }
+        attributes
+    }
+}
+
+impl Default for SvgRenderer {
+    fn default() -> SvgRenderer {
+        SvgRenderer::new()
+    }
+}
+
+// Splits a row into (start, length, style) runs of cells sharing a style.
+fn runs(cells: &[Cell]) -> Vec<(usize, usize, Style)> {
+    let mut runs: Vec<(usize, usize, Style)> = Vec::new();
+    for (column, cell) in cells.iter().enumerate() {
+        match runs.last_mut() {
+            Some((_, length, style)) if *style == cell.style => *length += 1,
+            _ => runs.push((column, 1, cell.style)),
+        }
+    }
+    runs
+}
+
+// At most two decimals, without trailing zeros.
+fn number(value: f64) -> String {
+    let formatted = format!("{:.2}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    String::from(trimmed)
+}
@@ -0,0 +1,16 @@
+use crate::{ASCIIControlCode, EscapeSequence};
+
+// Replaces every tab stop with stops at the given 1-based columns and leaves the cursor at the
+// start of the line, so rows can afterwards be written as tab separated columns.
+pub fn set_tab_stops(columns: &[u32]) -> String {
+    let mut sequence = EscapeSequence::ClearAllTabStopsSequence.to_string();
+    for &column in columns {
+        sequence.push_str(&ASCIIControlCode::CarriageReturnCode.to_string());
+        if column > 1 {
+            sequence.push_str(&EscapeSequence::CursorForwardSequence(column - 1).to_string());
+        }
+        sequence.push_str(&EscapeSequence::HorizontalTabSetSequence.to_string());
+    }
+    sequence.push_str(&ASCIIControlCode::CarriageReturnCode.to_string());
+    sequence
+}
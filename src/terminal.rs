@@ -0,0 +1,162 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::raw::{c_int, c_uint, c_ulong, c_ushort};
+use std::os::unix::io::AsRawFd;
+
+// The termios layout and flag values of the generic Linux ABI, shared by x86, ARM and RISC-V.
+// Other architectures (such as PowerPC and MIPS) use different ones and are not supported.
+const NCCS: usize = 32;
+const VTIME: usize = 5;
+const VMIN: usize = 6;
+const ISIG: c_uint = 0o1;
+const ICANON: c_uint = 0o2;
+const ECHO: c_uint = 0o10;
+const TCSANOW: c_int = 0;
+const TIOCGWINSZ: c_ulong = 0x5413;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Termios {
+    c_iflag: c_uint,
+    c_oflag: c_uint,
+    c_cflag: c_uint,
+    c_lflag: c_uint,
+    c_line: u8,
+    c_cc: [u8; NCCS],
+    c_ispeed: c_uint,
+    c_ospeed: c_uint,
+}
+
+#[repr(C)]
+struct WindowSize {
+    ws_row: c_ushort,
+    ws_col: c_ushort,
+    ws_xpixel: c_ushort,
+    ws_ypixel: c_ushort,
+}
+
+extern "C" {
+    fn tcgetattr(fd: c_int, termios: *mut Termios) -> c_int;
+    fn tcsetattr(fd: c_int, optional_actions: c_int, termios: *const Termios) -> c_int;
+    fn cfmakeraw(termios: *mut Termios);
+    fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminalMode {
+    NormalMode, // the settings the terminal had when the handle was opened
+    CbreakMode, // keys are read as they are pressed and not echoed; Ctrl-C still interrupts
+    RawMode,    // every byte is passed through as typed and output is not translated
+}
+
+// The controlling terminal, opened as /dev/tty so it works even when standard input or output
+// are redirected. Reading gives the bytes typed and writing goes straight to the screen. The
+// settings the terminal had when it was opened are put back when the handle is dropped,
+// including while unwinding from a panic. Nothing is put back when the process ends without
+// running destructors: with panic = "abort", after std::process::exit or on a fatal signal.
+// Programs built that way should keep a handle where their own panic hook can reach it and
+// call set_mode(TerminalMode::NormalMode) from there.
+pub struct Terminal {
+    tty: File,
+    original: Termios,
+    mode: TerminalMode,
+}
+
+impl Terminal {
+    pub fn new() -> io::Result<Terminal> {
+        let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let mut original = Termios {
+            c_iflag: 0,
+            c_oflag: 0,
+            c_cflag: 0,
+            c_lflag: 0,
+            c_line: 0,
+            c_cc: [0; NCCS],
+            c_ispeed: 0,
+            c_ospeed: 0,
+        };
+        check(unsafe { tcgetattr(tty.as_raw_fd(), &mut original) })?;
+        Ok(Terminal {
+            tty,
+            original,
+            mode: TerminalMode::NormalMode,
+        })
+    }
+
+    pub fn mode(&self) -> TerminalMode {
+        self.mode
+    }
+
+    // Raw and cbreak mode make reads return as soon as a single byte is available.
+    pub fn set_mode(&mut self, mode: TerminalMode) -> io::Result<()> {
+        let mut termios = self.original;
+        match mode {
+            TerminalMode::NormalMode => {}
+            TerminalMode::CbreakMode => {
+                termios.c_lflag &= !(ICANON | ECHO);
+                termios.c_lflag |= ISIG;
+                termios.c_cc[VMIN] = 1;
+                termios.c_cc[VTIME] = 0;
+            }
+            TerminalMode::RawMode => unsafe { cfmakeraw(&mut termios) },
+        }
+        check(unsafe { tcsetattr(self.tty.as_raw_fd(), TCSANOW, &termios) })?;
+        self.mode = mode;
+        Ok(())
+    }
+
+    // Columns and rows.
+    pub fn size(&self) -> io::Result<(usize, usize)> {
+        let size = self.window_size()?;
+        Ok((size.ws_col as usize, size.ws_row as usize))
+    }
+
+    // Width and height of the text area in pixels, which some terminals report as zero.
+    pub fn pixel_size(&self) -> io::Result<(usize, usize)> {
+        let size = self.window_size()?;
+        Ok((size.ws_xpixel as usize, size.ws_ypixel as usize))
+    }
+
+    fn window_size(&self) -> io::Result<WindowSize> {
+        let mut size = WindowSize {
+            ws_row: 0,
+            ws_col: 0,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        check(unsafe { ioctl(self.tty.as_raw_fd(), TIOCGWINSZ, &mut size) })?;
+        Ok(size)
+    }
+}
+
+impl Read for Terminal {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.tty.read(buffer)
+    }
+}
+
+impl Write for Terminal {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.tty.write(buffer)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.tty.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if self.mode != TerminalMode::NormalMode {
+            let _ = self.set_mode(TerminalMode::NormalMode);
+        }
+    }
+}
+
+fn check(result: c_int) -> io::Result<()> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{BlueColorCode, Color, ColorCode, GreenColorCode, RedColorCode, XTERM_SYSTEM_COLORS};
+
+// The colors a terminal shows for the default foreground and background and the sixteen
+// system colors, which renderers need to turn a Style into concrete colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub foreground: Color,
+    pub background: Color,
+    pub palette: [Color; 16],
+}
+
+impl Theme {
+    // xterm's defaults: light gray on black.
+    pub fn xterm() -> Theme {
+        let mut palette = [Color::RGB(0, 0, 0); 16];
+        for (color, (red, green, blue)) in palette.iter_mut().zip(XTERM_SYSTEM_COLORS.iter()) {
+            *color = Color::RGB(*red, *green, *blue);
+        }
+        Theme {
+            foreground: Color::RGB(229, 229, 229),
+            background: Color::RGB(0, 0, 0),
+            palette,
+        }
+    }
+
+    pub fn with_foreground(mut self, color: Color) -> Theme {
+        self.foreground = color;
+        self
+    }
+
+    pub fn with_background(mut self, color: Color) -> Theme {
+        self.background = color;
+        self
+    }
+
+    // Indices past 15 are ignored.
+    pub fn with_palette_color(mut self, color_code: ColorCode, color: Color) -> Theme {
+        if let Some(entry) = self.palette.get_mut(color_code as usize) {
+            *entry = color;
+        }
+        self
+    }
+
+    // System colors come from the palette; the rest of the 256 colors are fixed.
+    pub fn resolve(&self, color: Color) -> (RedColorCode, GreenColorCode, BlueColorCode) {
+        match color {
+            Color::Color256(color_code) if color_code < 16 => {
+                self.palette[color_code as usize].to_rgb()
+            }
+            color => color.to_rgb(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::xterm()
+    }
+}
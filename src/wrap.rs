@@ -0,0 +1,203 @@
+use crate::{layout::RenditionState, parser, width};
+
+// Spaces and joiners that must not be broken at.
+const NO_BREAK_CHARACTERS: [char; 5] = ['\u{a0}', '\u{2007}', '\u{202f}', '\u{2060}', '\u{feff}'];
+// Punctuation that stays on the line of the character before it.
+const CLOSING_PUNCTUATION: &str = ")]}.,;:!?、。，．：；！？）」』】〕〉》〗〙〛ー…‥";
+// Punctuation that stays on the line of the character after it.
+const OPENING_PUNCTUATION: &str = "([{（「『【〔〈《〖〘〚";
+
+enum Unit {
+    Cluster(String, usize),
+    Sequence(parser::Segment),
+    LineBreak,
+}
+
+// Wraps styled text to a number of columns. Lines break after spaces, after hyphens inside
+// words, after zero width spaces and around wide (CJK) characters; a word that is wider than
+// a whole line is split wherever it reaches the edge. Each line re-opens the style and link
+// that were active where it starts and closes them at its end, so the lines can be printed
+// on their own or in any order.
+pub struct TextWrapper {
+    width: usize,
+    hanging_indent: usize,
+}
+
+impl TextWrapper {
+    pub fn new(width: usize) -> TextWrapper {
+        TextWrapper {
+            width: width.max(1),
+            hanging_indent: 0,
+        }
+    }
+
+    // Indents the lines a wrapped line continues on. Lines after an explicit newline are not
+    // indented. The indent is cut to one column less than the width so that every line has
+    // room for some text.
+    pub fn with_hanging_indent(mut self, columns: usize) -> TextWrapper {
+        self.hanging_indent = columns.min(self.width - 1);
+        self
+    }
+
+    pub fn wrap(&self, text: &str) -> Vec<String> {
+        let units = units(text);
+        let mut lines = Vec::new();
+        let mut state = RenditionState::default();
+        for (start, end, continuation) in self.line_ranges(&units) {
+            let mut line = String::new();
+            if continuation {
+                line.push_str(&" ".repeat(self.hanging_indent));
+            }
+            line.push_str(&state.opening());
+            // Spaces at the end of a line are dropped, sequences among them are not.
+            let content_end = (start..end)
+                .rev()
+                .find(|&index| match &units[index] {
+                    Unit::Cluster(cluster, _) => !is_space(cluster),
+                    _ => false,
+                })
+                .map_or(start, |index| index + 1);
+            for (index, unit) in units.iter().enumerate().take(end).skip(start) {
+                match unit {
+                    Unit::Cluster(cluster, _) if index < content_end => line.push_str(cluster),
+                    Unit::Sequence(segment) => {
+                        state.apply(segment);
+                        line.push_str(&segment.to_string());
+                    }
+                    _ => {}
+                }
+            }
+            line.push_str(&state.closing());
+            lines.push(line);
+        }
+        lines
+    }
+
+    // The wrapped lines joined with newlines.
+    pub fn fill(&self, text: &str) -> String {
+        self.wrap(text).join("\n")
+    }
+
+    // Greedy line filling: (start, end, continuation) ranges of units, one per line.
+    fn line_ranges(&self, units: &[Unit]) -> Vec<(usize, usize, bool)> {
+        let mut ranges = Vec::new();
+        let mut line_start = 0;
+        let mut line_width = 0;
+        let mut continuation = false;
+        let mut last_break: Option<usize> = None;
+        let mut previous: (Option<&str>, Option<&str>) = (None, None);
+        for (index, unit) in units.iter().enumerate() {
+            let (cluster, cluster_width) = match unit {
+                Unit::Cluster(cluster, cluster_width) => (cluster.as_str(), *cluster_width),
+                Unit::Sequence(_) => continue,
+                Unit::LineBreak => {
+                    ranges.push((line_start, index, continuation));
+                    line_start = index + 1;
+                    line_width = 0;
+                    continuation = false;
+                    last_break = None;
+                    previous = (None, None);
+                    continue;
+                }
+            };
+            if is_break_opportunity(previous, cluster) {
+                last_break = Some(index);
+            }
+            let available = if continuation {
+                self.width - self.hanging_indent
+            } else {
+                self.width
+            };
+            if !is_space(cluster) && line_width > 0 && line_width + cluster_width > available {
+                let split = match last_break {
+                    Some(split) if split > line_start && split < index => split,
+                    _ => index,
+                };
+                ranges.push((line_start, split, continuation));
+                line_start = split;
+                continuation = true;
+                last_break = None;
+                line_width = units[split..index]
+                    .iter()
+                    .map(|unit| match unit {
+                        Unit::Cluster(_, cluster_width) => *cluster_width,
+                        _ => 0,
+                    })
+                    .sum();
+                // What moved down may still be too wide for the indented line.
+                let available = self.width - self.hanging_indent;
+                if line_width > 0 && line_width + cluster_width > available {
+                    ranges.push((line_start, index, continuation));
+                    line_start = index;
+                    line_width = 0;
+                }
+            }
+            line_width += cluster_width;
+            previous = (previous.1, Some(cluster));
+        }
+        ranges.push((line_start, units.len(), continuation));
+        ranges
+    }
+}
+
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    TextWrapper::new(width).wrap(text)
+}
+
+// Tabs count as a single space; carriage returns and other control codes are passed through
+// without taking up any width.
+fn units(text: &str) -> Vec<Unit> {
+    let mut units = Vec::new();
+    for segment in parser::parse(text) {
+        match segment {
+            parser::Segment::TextSegment(text) => units.extend(
+                width::clusters(&text)
+                    .into_iter()
+                    .map(|(cluster, cluster_width)| Unit::Cluster(cluster, cluster_width)),
+            ),
+            parser::Segment::ControlCodeSegment('\n') => units.push(Unit::LineBreak),
+            parser::Segment::ControlCodeSegment('\t') => {
+                units.push(Unit::Cluster(String::from(" "), 1))
+            }
+            segment => units.push(Unit::Sequence(segment)),
+        }
+    }
+    units
+}
+
+fn is_space(cluster: &str) -> bool {
+    cluster
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_whitespace() && !NO_BREAK_CHARACTERS.contains(&c))
+}
+
+// Whether a line may break between the clusters before `cluster` and `cluster` itself. A
+// simplified form of the Unicode line breaking algorithm (UAX #14).
+fn is_break_opportunity(previous: (Option<&str>, Option<&str>), cluster: &str) -> bool {
+    let (before_previous, previous) = match previous {
+        (before_previous, Some(previous)) => (before_previous, previous),
+        _ => return false,
+    };
+    let last = previous.chars().last().unwrap_or(' ');
+    let next = cluster.chars().next().unwrap_or(' ');
+    if is_space(cluster) || NO_BREAK_CHARACTERS.contains(&last) {
+        return false;
+    }
+    if NO_BREAK_CHARACTERS.contains(&next) || CLOSING_PUNCTUATION.contains(next) {
+        return false;
+    }
+    if is_space(previous) || last == '\u{200b}' {
+        return true;
+    }
+    if OPENING_PUNCTUATION.contains(last) {
+        return false;
+    }
+    // Hyphens inside a word, as in "escape-aware", but not the sign of "-1".
+    if (last == '-' || last == '\u{2010}') && next.is_alphanumeric() {
+        return before_previous
+            .and_then(|cluster| cluster.chars().last())
+            .is_some_and(char::is_alphanumeric);
+    }
+    width::text_width(previous) == 2 || width::text_width(cluster) == 2
+}
@@ -0,0 +1,196 @@
+use ansi_escape_codes::{AsciicastEvent, AsciicastEventCode, AsciicastHeader, Player, Recorder};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+fn event(time: f64, code: AsciicastEventCode, data: &str) -> AsciicastEvent {
+    AsciicastEvent {
+        time,
+        code,
+        data: String::from(data),
+    }
+}
+
+fn cast(events: &[AsciicastEvent]) -> String {
+    let mut cast = format!("{}\n", AsciicastHeader::new(80, 24));
+    for event in events {
+        cast.push_str(&format!("{}\n", event));
+    }
+    cast
+}
+
+fn play(player: &Player) -> String {
+    let mut output = Vec::new();
+    player.play(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn recordings_play_back() {
+    let header = AsciicastHeader::new(100, 30)
+        .with_timestamp(1_700_000_000)
+        .with_title("demo \"quoted\"")
+        .with_env("TERM", "xterm-256color");
+    let mut recorder = Recorder::new(Vec::new(), header.clone()).unwrap();
+    write!(recorder, "\x1b[1mhello\x1b[0m\r\n").unwrap();
+    recorder.record_input("q").unwrap();
+    recorder.record_marker("done").unwrap();
+    recorder.record_resize(120, 40).unwrap();
+    write!(recorder, "tab\tand ✓").unwrap();
+    let recording = recorder.into_inner().unwrap();
+
+    let player = Player::read(recording.as_slice()).unwrap();
+    assert_eq!(player.header(), &header);
+    let events: Vec<(AsciicastEventCode, &str)> = player
+        .events()
+        .iter()
+        .map(|event| (event.code, event.data.as_str()))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            (AsciicastEventCode::OutputCode, "\x1b[1mhello\x1b[0m\r\n"),
+            (AsciicastEventCode::InputCode, "q"),
+            (AsciicastEventCode::MarkerCode, "done"),
+            (AsciicastEventCode::ResizeCode, "120x40"),
+            (AsciicastEventCode::OutputCode, "tab\tand ✓"),
+        ]
+    );
+    assert!(player
+        .events()
+        .windows(2)
+        .all(|pair| pair[0].time <= pair[1].time));
+    assert_eq!(play(&player), "\x1b[1mhello\x1b[0m\r\ntab\tand ✓");
+}
+
+#[test]
+fn characters_split_across_writes_are_recorded_whole() {
+    let mut recorder = Recorder::new(Vec::new(), AsciicastHeader::new(80, 24)).unwrap();
+    let bytes = "a✓".as_bytes();
+    recorder.write_all(&bytes[..2]).unwrap();
+    recorder.write_all(&bytes[2..]).unwrap();
+    let recording = recorder.into_inner().unwrap();
+    let player = Player::read(recording.as_slice()).unwrap();
+    let data: Vec<&str> = player
+        .events()
+        .iter()
+        .map(|event| event.data.as_str())
+        .collect();
+    assert_eq!(data, vec!["a", "✓"]);
+}
+
+#[test]
+fn an_unfinished_character_is_recorded_when_the_recorder_ends() {
+    let bytes = "✓".as_bytes();
+    let mut recorder = Recorder::new(Vec::new(), AsciicastHeader::new(80, 24)).unwrap();
+    recorder.write_all(&bytes[..2]).unwrap();
+    let recording = recorder.into_inner().unwrap();
+    let player = Player::read(recording.as_slice()).unwrap();
+    assert_eq!(player.events().len(), 1);
+    assert_eq!(player.events()[0].data, "\u{fffd}");
+
+    let mut recording = Vec::new();
+    {
+        let mut recorder = Recorder::new(&mut recording, AsciicastHeader::new(80, 24)).unwrap();
+        recorder.write_all(&bytes[..1]).unwrap();
+    }
+    let player = Player::read(recording.as_slice()).unwrap();
+    assert_eq!(player.events().len(), 1);
+    assert_eq!(player.events()[0].data, "\u{fffd}");
+}
+
+#[test]
+fn malformed_files_are_rejected() {
+    let header = AsciicastHeader::new(80, 24).to_string();
+    let cases = [
+        String::new(),
+        String::from("{\"version\": 1, \"width\": 80, \"height\": 24}"),
+        String::from("[2, 80, 24]"),
+        format!("{}\n[0.5, \"o\"]", header),
+        format!("{}\n[0.5, \"x\", \"data\"]", header),
+        format!("{}\n[0.5, \"o\", \"unterminated]", header),
+        format!("{}\n[0.5, \"o\", \"data\"] trailing", header),
+    ];
+    for cast in cases.iter() {
+        assert!(Player::read(cast.as_bytes()).is_err(), "{:?}", cast);
+    }
+}
+
+#[test]
+fn deeply_nested_lines_are_rejected() {
+    let header = AsciicastHeader::new(80, 24).to_string();
+    let cast = format!("{}\n{}", header, "[".repeat(200_000));
+    assert!(Player::read(cast.as_bytes()).is_err());
+
+    let nested = |depth: usize| {
+        format!(
+            "{{\"version\": 2, \"width\": 80, \"height\": 24, \"theme\": {}{}}}",
+            "[".repeat(depth),
+            "]".repeat(depth)
+        )
+    };
+    assert!(AsciicastHeader::parse(&nested(127)).is_some());
+    assert!(AsciicastHeader::parse(&nested(128)).is_none());
+}
+
+#[test]
+fn numbers_must_be_finite() {
+    for time in ["inf", "-inf", "NaN", "infinity", "1e999"].iter() {
+        let line = format!("[{}, \"o\", \"x\"]", time);
+        assert_eq!(AsciicastEvent::parse(&line), None, "{}", line);
+    }
+    assert_eq!(
+        AsciicastEvent::parse("[1e2, \"o\", \"\\u00e9\\ud83d\\ude00\"]"),
+        Some(event(100.0, AsciicastEventCode::OutputCode, "é😀"))
+    );
+}
+
+#[test]
+fn unicode_escapes_take_four_hex_digits() {
+    for data in ["\\u+041", "\\u-041", "\\u 041", "\\u04"].iter() {
+        let line = format!("[1, \"o\", \"{}\"]", data);
+        assert_eq!(AsciicastEvent::parse(&line), None, "{}", line);
+    }
+    assert_eq!(
+        AsciicastEvent::parse("[1, \"o\", \"\\u0041\"]"),
+        Some(event(1.0, AsciicastEventCode::OutputCode, "A"))
+    );
+}
+
+#[test]
+fn events_too_far_away_are_an_error() {
+    let cast = cast(&[
+        event(0.0, AsciicastEventCode::OutputCode, "now"),
+        event(1e300, AsciicastEventCode::OutputCode, "never"),
+    ]);
+    let player = Player::read(cast.as_bytes()).unwrap();
+    let mut output = Vec::new();
+    assert!(player.play(&mut output).is_err());
+    assert_eq!(output, b"now");
+}
+
+#[test]
+fn invalid_speeds_are_ignored() {
+    let cast = cast(&[event(0.0, AsciicastEventCode::OutputCode, "x")]);
+    for speed in [0.0, -1.0, f64::INFINITY, f64::NAN].iter() {
+        let player = Player::read(cast.as_bytes()).unwrap().with_speed(*speed);
+        assert_eq!(play(&player), "x");
+    }
+}
+
+#[test]
+fn speed_and_idle_time_limit_shorten_pauses() {
+    let cast = cast(&[
+        event(0.2, AsciicastEventCode::OutputCode, "a"),
+        event(1000.0, AsciicastEventCode::OutputCode, "b"),
+    ]);
+    let player = Player::read(cast.as_bytes())
+        .unwrap()
+        .with_speed(2.0)
+        .with_idle_time_limit(0.2)
+        .with_idle_time_limit(-1.0);
+    let start = Instant::now();
+    assert_eq!(play(&player), "ab");
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(10), "{:?}", elapsed);
+}
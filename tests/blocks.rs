@@ -0,0 +1,86 @@
+use ansi_escape_codes::{render_braille, render_half_blocks, BufferSizeError, ColorDepth};
+
+const RED: [u8; 3] = [255, 0, 0];
+const BLUE: [u8; 3] = [0, 0, 255];
+
+fn pixels(colors: &[[u8; 3]]) -> Vec<u8> {
+    colors.iter().flat_map(|color| color.to_vec()).collect()
+}
+
+#[test]
+fn each_cell_shows_two_pixel_rows() {
+    let rgb = pixels(&[RED, RED, BLUE, RED]);
+    assert_eq!(
+        render_half_blocks(2, 2, &rgb, ColorDepth::TrueColorDepth),
+        Ok(String::from(
+            "\x1b[38;2;255;0;0;48;2;0;0;255m▀\x1b[38;2;255;0;0;48;2;255;0;0m▀\x1b[0m"
+        ))
+    );
+}
+
+#[test]
+fn colors_are_only_sent_when_they_change() {
+    let rgb = pixels(&[RED, RED, RED, BLUE, BLUE, BLUE]);
+    assert_eq!(
+        render_half_blocks(3, 2, &rgb, ColorDepth::TrueColorDepth),
+        Ok(String::from("\x1b[38;2;255;0;0;48;2;0;0;255m▀▀▀\x1b[0m"))
+    );
+}
+
+#[test]
+fn an_odd_last_row_keeps_the_default_background() {
+    let rgb = pixels(&[RED, BLUE, RED]);
+    assert_eq!(
+        render_half_blocks(1, 3, &rgb, ColorDepth::TrueColorDepth),
+        Ok(String::from(
+            "\x1b[38;2;255;0;0;48;2;0;0;255m▀\x1b[0m\n\x1b[38;2;255;0;0;49m▀\x1b[0m"
+        ))
+    );
+}
+
+#[test]
+fn colors_are_reduced_to_the_color_depth() {
+    let rgb = pixels(&[[250, 10, 5], [0, 0, 120]]);
+    assert_eq!(
+        render_half_blocks(1, 2, &rgb, ColorDepth::Color256Depth),
+        Ok(String::from("\x1b[38;5;196;48;5;18m▀\x1b[0m"))
+    );
+    assert_eq!(
+        render_half_blocks(1, 2, &rgb, ColorDepth::Color16Depth),
+        Ok(String::from("\x1b[91;44m▀\x1b[0m"))
+    );
+}
+
+#[test]
+fn braille_cells_cover_two_by_four_dots() {
+    let mut dots = vec![false; 3 * 5];
+    assert_eq!(render_braille(3, 5, &dots), Ok(String::from("⠀⠀\n⠀⠀")));
+    // The corners of the first cell, the top of the second and the one dot below them.
+    dots[0] = true;
+    dots[3 * 3] = true;
+    dots[1] = true;
+    dots[3 * 3 + 1] = true;
+    dots[2] = true;
+    dots[3 * 4 + 2] = true;
+    assert_eq!(render_braille(3, 5, &dots), Ok(String::from("⣉⠁\n⠀⠁")));
+    assert_eq!(render_braille(2, 4, &[true; 8]), Ok(String::from("⣿")));
+}
+
+#[test]
+fn short_buffers_are_an_error() {
+    assert_eq!(
+        render_half_blocks(2, 2, &[0; 11], ColorDepth::TrueColorDepth),
+        Err(BufferSizeError {
+            expected: 12,
+            actual: 11
+        })
+    );
+    assert_eq!(
+        render_braille(2, 4, &[true; 7]),
+        Err(BufferSizeError {
+            expected: 8,
+            actual: 7
+        })
+    );
+    assert_eq!(render_braille(0, 0, &[]), Ok(String::new()));
+}
@@ -0,0 +1,67 @@
+use ansi_escape_codes::{
+    dec_special_graphics_to_unicode, encode_dec_special_graphics, unicode_to_dec_special_graphics,
+    CharacterSet, EscapeSequence, GraphicSet,
+};
+
+#[test]
+fn character_sets_are_designated_to_each_graphic_set() {
+    let cases = [
+        (
+            GraphicSet::G0GraphicSet,
+            CharacterSet::USASCIICharacterSet,
+            "\x1b(B",
+        ),
+        (
+            GraphicSet::G1GraphicSet,
+            CharacterSet::DECSpecialGraphicsCharacterSet,
+            "\x1b)0",
+        ),
+        (
+            GraphicSet::G2GraphicSet,
+            CharacterSet::UnitedKingdomCharacterSet,
+            "\x1b*A",
+        ),
+        (
+            GraphicSet::G3GraphicSet,
+            CharacterSet::DECSupplementalCharacterSet,
+            "\x1b+<",
+        ),
+    ];
+    for (graphic_set, character_set, expected) in cases.iter() {
+        assert_eq!(
+            EscapeSequence::DesignateCharacterSetSequence(*graphic_set, *character_set).to_string(),
+            *expected
+        );
+    }
+}
+
+#[test]
+fn single_shifts_select_g2_and_g3() {
+    assert_eq!(EscapeSequence::SingleShiftTwoSequence.to_string(), "\x1bN");
+    assert_eq!(
+        EscapeSequence::SingleShiftThreeSequence.to_string(),
+        "\x1bO"
+    );
+}
+
+#[test]
+fn line_drawing_is_mapped_both_ways() {
+    assert_eq!(dec_special_graphics_to_unicode('q'), '─');
+    assert_eq!(dec_special_graphics_to_unicode('A'), 'A');
+    assert_eq!(unicode_to_dec_special_graphics('┌'), Some('l'));
+    assert_eq!(unicode_to_dec_special_graphics('╔'), Some('l'));
+    assert_eq!(unicode_to_dec_special_graphics('é'), None);
+}
+
+#[test]
+fn line_drawing_runs_are_shifted_out_to_g1() {
+    assert_eq!(
+        encode_dec_special_graphics("┌─┐\n│é│ ok"),
+        "\x1b)0\x0elqk\x0f\n\x0ex\x0fé\x0ex\x0f ok"
+    );
+    assert_eq!(
+        encode_dec_special_graphics("plain ascii"),
+        "\x1b)0plain ascii"
+    );
+    assert_eq!(encode_dec_special_graphics("══"), "\x1b)0\x0eqq\x0f");
+}
@@ -0,0 +1,44 @@
+use ansi_escape_codes::ASCIIControlCode;
+
+#[test]
+fn every_control_code_is_its_byte() {
+    let codes = [
+        ASCIIControlCode::NullCode,
+        ASCIIControlCode::StartOfHeadingCode,
+        ASCIIControlCode::StartOfTextCode,
+        ASCIIControlCode::EndofTextCode,
+        ASCIIControlCode::EndOfTransmissionCode,
+        ASCIIControlCode::EnquiryCode,
+        ASCIIControlCode::AcknowledgeCode,
+        ASCIIControlCode::BellCode,
+        ASCIIControlCode::BackspaceCode,
+        ASCIIControlCode::HorizontalTabCode,
+        ASCIIControlCode::LineFeedCode,
+        ASCIIControlCode::VerticalTabCode,
+        ASCIIControlCode::FormFeedCode,
+        ASCIIControlCode::CarriageReturnCode,
+        ASCIIControlCode::ShiftOutCode,
+        ASCIIControlCode::ShiftInCode,
+        ASCIIControlCode::DataLinkEscapeCode,
+        ASCIIControlCode::DeviceControlOneCode,
+        ASCIIControlCode::DeviceControlTwoCode,
+        ASCIIControlCode::DeviceControlThreeCode,
+        ASCIIControlCode::DeviceControlFourCode,
+        ASCIIControlCode::NegativeAcknowledgeCode,
+        ASCIIControlCode::SynchronousIdleCode,
+        ASCIIControlCode::EndOfTransmissionBlockCode,
+        ASCIIControlCode::CancelCode,
+        ASCIIControlCode::EndOfMediumCode,
+        ASCIIControlCode::SubsituteCode,
+        ASCIIControlCode::EscapeCode,
+        ASCIIControlCode::FileSeperatorCode,
+        ASCIIControlCode::GroupSeperatorCode,
+        ASCIIControlCode::RecordSeperatorCode,
+        ASCIIControlCode::UnitSeperatorCode,
+    ];
+    for (byte, code) in codes.iter().enumerate() {
+        assert_eq!(code.to_string(), (byte as u8 as char).to_string());
+    }
+    assert_eq!(ASCIIControlCode::SpaceCode.to_string(), " ");
+    assert_eq!(ASCIIControlCode::DeleteCode.to_string(), "\x7f");
+}
@@ -0,0 +1,216 @@
+use ansi_escape_codes::{CursorStyle, CursorStyleGuard, EscapeSequence};
+
+// Final bytes from ECMA-48 section 8.3; parameters equal to the default of 1 are omitted.
+fn cursor_table() -> Vec<(EscapeSequence, &'static str)> {
+    vec![
+        (EscapeSequence::CursorUpSequence(1), "\x1b[A"),
+        (EscapeSequence::CursorUpSequence(3), "\x1b[3A"),
+        (EscapeSequence::CursorDownSequence(1), "\x1b[B"),
+        (EscapeSequence::CursorDownSequence(4), "\x1b[4B"),
+        (EscapeSequence::CursorForwardSequence(1), "\x1b[C"),
+        (EscapeSequence::CursorForwardSequence(10), "\x1b[10C"),
+        (EscapeSequence::CursorBackSequence(1), "\x1b[D"),
+        (EscapeSequence::CursorBackSequence(2), "\x1b[2D"),
+        (EscapeSequence::CursorNextLineSequence(1), "\x1b[E"),
+        (EscapeSequence::CursorNextLineSequence(2), "\x1b[2E"),
+        (EscapeSequence::CursorPreviousLineSequence(1), "\x1b[F"),
+        (EscapeSequence::CursorPreviousLineSequence(2), "\x1b[2F"),
+        (
+            EscapeSequence::CursorHorizontalAbsoluteSequence(1),
+            "\x1b[G",
+        ),
+        (
+            EscapeSequence::CursorHorizontalAbsoluteSequence(40),
+            "\x1b[40G",
+        ),
+        (EscapeSequence::CursorPositionSequence(1, 1), "\x1b[H"),
+        (EscapeSequence::CursorPositionSequence(5, 1), "\x1b[5H"),
+        (EscapeSequence::CursorPositionSequence(1, 7), "\x1b[;7H"),
+        (EscapeSequence::CursorPositionSequence(5, 7), "\x1b[5;7H"),
+        (EscapeSequence::CursorForwardTabulationSequence(1), "\x1b[I"),
+        (
+            EscapeSequence::CursorForwardTabulationSequence(2),
+            "\x1b[2I",
+        ),
+        (
+            EscapeSequence::CursorBackwardTabulationSequence(1),
+            "\x1b[Z",
+        ),
+        (
+            EscapeSequence::CursorBackwardTabulationSequence(3),
+            "\x1b[3Z",
+        ),
+        (EscapeSequence::CursorLineTabulationSequence(1), "\x1b[Y"),
+        (EscapeSequence::CursorLineTabulationSequence(2), "\x1b[2Y"),
+        (
+            EscapeSequence::CharacterPositionAbsoluteSequence(1),
+            "\x1b[`",
+        ),
+        (
+            EscapeSequence::CharacterPositionAbsoluteSequence(12),
+            "\x1b[12`",
+        ),
+        (
+            EscapeSequence::CharacterPositionForwardSequence(1),
+            "\x1b[a",
+        ),
+        (
+            EscapeSequence::CharacterPositionForwardSequence(6),
+            "\x1b[6a",
+        ),
+        (
+            EscapeSequence::CharacterPositionBackwardSequence(1),
+            "\x1b[j",
+        ),
+        (
+            EscapeSequence::CharacterPositionBackwardSequence(6),
+            "\x1b[6j",
+        ),
+        (
+            EscapeSequence::VerticalPositionAbsoluteSequence(1),
+            "\x1b[d",
+        ),
+        (
+            EscapeSequence::VerticalPositionAbsoluteSequence(24),
+            "\x1b[24d",
+        ),
+        (EscapeSequence::VerticalPositionForwardSequence(1), "\x1b[e"),
+        (
+            EscapeSequence::VerticalPositionForwardSequence(3),
+            "\x1b[3e",
+        ),
+        (
+            EscapeSequence::VerticalPositionBackwardSequence(1),
+            "\x1b[k",
+        ),
+        (
+            EscapeSequence::VerticalPositionBackwardSequence(3),
+            "\x1b[3k",
+        ),
+        (
+            EscapeSequence::HorizontalVerticalPositionSequence(1, 1),
+            "\x1b[f",
+        ),
+        (
+            EscapeSequence::HorizontalVerticalPositionSequence(2, 1),
+            "\x1b[2f",
+        ),
+        (
+            EscapeSequence::HorizontalVerticalPositionSequence(1, 9),
+            "\x1b[;9f",
+        ),
+        (
+            EscapeSequence::HorizontalVerticalPositionSequence(2, 9),
+            "\x1b[2;9f",
+        ),
+        (EscapeSequence::EraseScreenAfterCursorSequence, "\x1b[J"),
+        (EscapeSequence::EraseScreenBeforeCursorSequence, "\x1b[1J"),
+        (EscapeSequence::EraseScreenSequence, "\x1b[2J"),
+        (EscapeSequence::EraseScreenAndScrollbackSequence, "\x1b[3J"),
+        (EscapeSequence::EraseLineAfterCursorSequence, "\x1b[K"),
+        (EscapeSequence::EraseLineBeforeCursorSequence, "\x1b[1K"),
+        (EscapeSequence::EraseLineSequence, "\x1b[2K"),
+        (EscapeSequence::ScrollUpSequence(1), "\x1b[S"),
+        (EscapeSequence::ScrollUpSequence(3), "\x1b[3S"),
+        (EscapeSequence::ScrollDownSequence(1), "\x1b[T"),
+        (EscapeSequence::ScrollDownSequence(3), "\x1b[3T"),
+        (EscapeSequence::InsertLineSequence(1), "\x1b[L"),
+        (EscapeSequence::InsertLineSequence(2), "\x1b[2L"),
+        (EscapeSequence::DeleteLineSequence(1), "\x1b[M"),
+        (EscapeSequence::DeleteLineSequence(2), "\x1b[2M"),
+        (EscapeSequence::InsertCharacterSequence(1), "\x1b[@"),
+        (EscapeSequence::InsertCharacterSequence(4), "\x1b[4@"),
+        (EscapeSequence::DeleteCharacterSequence(1), "\x1b[P"),
+        (EscapeSequence::DeleteCharacterSequence(4), "\x1b[4P"),
+        (EscapeSequence::EraseCharacterSequence(1), "\x1b[X"),
+        (EscapeSequence::EraseCharacterSequence(4), "\x1b[4X"),
+        (EscapeSequence::RepeatSequence(1), "\x1b[b"),
+        (EscapeSequence::RepeatSequence(5), "\x1b[5b"),
+        (EscapeSequence::SaveCursorPositionSequence, "\x1b[s"),
+        (EscapeSequence::RestoreCursorPositionSequence, "\x1b[u"),
+    ]
+}
+
+#[test]
+fn every_cursor_sequence_uses_its_documented_final_byte() {
+    for (sequence, expected) in cursor_table() {
+        assert_eq!(sequence.to_string(), expected);
+    }
+}
+
+#[test]
+fn zero_is_emitted_rather_than_treated_as_the_default() {
+    assert_eq!(EscapeSequence::CursorUpSequence(0).to_string(), "\x1b[0A");
+    assert_eq!(
+        EscapeSequence::CursorPositionSequence(0, 0).to_string(),
+        "\x1b[0;0H"
+    );
+}
+
+#[test]
+fn cursor_styles_use_decscusr() {
+    let cases = [
+        (CursorStyle::DefaultCursorStyle, "\x1b[0 q"),
+        (CursorStyle::BlinkingBlockCursorStyle, "\x1b[1 q"),
+        (CursorStyle::SteadyBlockCursorStyle, "\x1b[2 q"),
+        (CursorStyle::BlinkingUnderlineCursorStyle, "\x1b[3 q"),
+        (CursorStyle::SteadyUnderlineCursorStyle, "\x1b[4 q"),
+        (CursorStyle::BlinkingBarCursorStyle, "\x1b[5 q"),
+        (CursorStyle::SteadyBarCursorStyle, "\x1b[6 q"),
+    ];
+    for (cursor_style, expected) in cases.iter() {
+        assert_eq!(
+            EscapeSequence::SetCursorStyleSequence(*cursor_style).to_string(),
+            *expected
+        );
+    }
+}
+
+#[test]
+fn the_cursor_is_shown_and_hidden_with_mode_25() {
+    assert_eq!(EscapeSequence::ShowCursorSequence.to_string(), "\x1b[?25h");
+    assert_eq!(EscapeSequence::HideCursorSequence.to_string(), "\x1b[?25l");
+}
+
+#[test]
+fn dropping_the_guard_restores_the_default_cursor_style() {
+    let mut output = Vec::new();
+    {
+        let mut guard =
+            CursorStyleGuard::new(&mut output, CursorStyle::SteadyBarCursorStyle).unwrap();
+        guard
+            .set(CursorStyle::BlinkingUnderlineCursorStyle)
+            .unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\x1b[6 q\x1b[3 q\x1b[0 q"
+    );
+}
+
+#[test]
+fn scrolling_margins_are_set_and_reset() {
+    let cases = [
+        (
+            EscapeSequence::SetTopBottomMarginsSequence(2, 20),
+            "\x1b[2;20r",
+        ),
+        (EscapeSequence::ResetTopBottomMarginsSequence, "\x1b[r"),
+        (
+            EscapeSequence::EnableLeftRightMarginModeSequence,
+            "\x1b[?69h",
+        ),
+        (
+            EscapeSequence::SetLeftRightMarginsSequence(5, 60),
+            "\x1b[5;60s",
+        ),
+        (
+            EscapeSequence::DisableLeftRightMarginModeSequence,
+            "\x1b[?69l",
+        ),
+        (EscapeSequence::ReverseIndexSequence, "\x1bM"),
+    ];
+    for (sequence, expected) in cases.iter() {
+        assert_eq!(sequence.to_string(), *expected);
+    }
+}
@@ -0,0 +1,137 @@
+use ansi_escape_codes::{Color, HtmlConverter, HtmlMode, Theme};
+
+fn inline(input: &str) -> String {
+    HtmlConverter::new().convert(input)
+}
+
+fn classes(input: &str) -> String {
+    HtmlConverter::new()
+        .with_mode(HtmlMode::ClassMode)
+        .convert(input)
+}
+
+#[test]
+fn text_is_escaped() {
+    assert_eq!(
+        inline("<b> & \"quoted\" 'single'"),
+        "&lt;b&gt; &amp; &quot;quoted&quot; &#39;single&#39;"
+    );
+    assert_eq!(
+        inline("\x1b[1m<\x1b[0m>"),
+        "<span style=\"font-weight:bold\">&lt;</span>&gt;"
+    );
+}
+
+#[test]
+fn spans_are_only_opened_when_the_style_changes() {
+    assert_eq!(
+        inline("\x1b[31mred\x1b[31m still\x1b[0m\nplain\x1b[2J"),
+        "<span style=\"color:#cd0000\">red still</span>\nplain"
+    );
+}
+
+#[test]
+fn palette_and_truecolor_are_written_as_hex() {
+    assert_eq!(
+        inline("\x1b[38;5;196;48;2;1;2;3mx"),
+        "<span style=\"color:#ff0000;background-color:#010203\">x</span>"
+    );
+    assert_eq!(
+        inline("\x1b[38;5;232mx"),
+        "<span style=\"color:#080808\">x</span>"
+    );
+}
+
+#[test]
+fn reverse_video_swaps_the_colors() {
+    assert_eq!(
+        inline("\x1b[7mx"),
+        "<span style=\"color:#000000;background-color:#e5e5e5\">x</span>"
+    );
+    assert_eq!(
+        inline("\x1b[31;7mx"),
+        "<span style=\"color:#000000;background-color:#cd0000\">x</span>"
+    );
+    assert_eq!(
+        classes("\x1b[7mx\x1b[44mx"),
+        concat!(
+            "<span class=\"ansi-fg-inverse ansi-bg-inverse\">x</span>",
+            "<span class=\"ansi-fg-4 ansi-bg-inverse\">x</span>"
+        )
+    );
+}
+
+#[test]
+fn the_theme_supplies_the_system_colors_and_defaults() {
+    let theme = Theme::xterm()
+        .with_palette_color(1, Color::RGB(1, 2, 3))
+        .with_foreground(Color::RGB(16, 16, 16))
+        .with_background(Color::RGB(250, 250, 250));
+    let converter = HtmlConverter::new().with_theme(theme);
+    assert_eq!(
+        converter.convert("\x1b[31mx\x1b[0;7my"),
+        concat!(
+            "<span style=\"color:#010203\">x</span>",
+            "<span style=\"color:#fafafa;background-color:#101010\">y</span>"
+        )
+    );
+    let stylesheet = converter.with_mode(HtmlMode::ClassMode).stylesheet();
+    assert!(stylesheet.starts_with(".ansi { color: #101010; background-color: #fafafa; }\n"));
+    assert!(stylesheet.contains(".ansi-fg-1 { color: #010203; }\n"));
+    assert!(stylesheet.contains(".ansi-bg-196 { background-color: #ff0000; }\n"));
+}
+
+#[test]
+fn class_mode_uses_classes_where_it_can() {
+    assert_eq!(
+        classes("\x1b[1;3;31;42mx"),
+        "<span class=\"ansi-fg-1 ansi-bg-2 ansi-bold ansi-italic\">x</span>"
+    );
+    assert_eq!(
+        classes("\x1b[4:3;38;2;1;2;3mx"),
+        "<span class=\"ansi-underline ansi-underline-curly\" style=\"color:#010203\">x</span>"
+    );
+    assert_eq!(
+        HtmlConverter::new()
+            .with_mode(HtmlMode::ClassMode)
+            .with_class_prefix("t")
+            .convert("\x1b[9mx"),
+        "<span class=\"t-strike\">x</span>"
+    );
+}
+
+#[test]
+fn inline_styles_combine_text_decorations() {
+    assert_eq!(
+        inline("\x1b[4:2;9;53;58;5;1mx"),
+        concat!(
+            "<span style=\"text-decoration-line:underline line-through overline;",
+            "text-decoration-style:double;text-decoration-color:#cd0000\">x</span>"
+        )
+    );
+}
+
+#[test]
+fn blinking_is_kept_in_both_modes() {
+    assert_eq!(
+        inline("\x1b[5mx"),
+        "<span style=\"text-decoration-line:blink\">x</span>"
+    );
+    assert_eq!(
+        inline("\x1b[4;5mx"),
+        "<span style=\"text-decoration-line:underline blink\">x</span>"
+    );
+    assert_eq!(classes("\x1b[5mx"), "<span class=\"ansi-blink\">x</span>");
+}
+
+#[test]
+fn only_links_with_safe_schemes_are_kept() {
+    assert_eq!(
+        inline("\x1b]8;;https://example.com/?a=1&b=2\x1b\\site\x1b]8;;\x1b\\"),
+        "<a href=\"https://example.com/?a=1&amp;b=2\">site</a>"
+    );
+    assert_eq!(
+        inline("\x1b]8;;javascript:alert(1)\x1b\\site\x1b]8;;\x1b\\"),
+        "site"
+    );
+}
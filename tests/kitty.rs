@@ -0,0 +1,162 @@
+use ansi_escape_codes::parser::parse;
+use ansi_escape_codes::{
+    BufferSizeError, KittyDeletion, KittyGraphicsCommand, KittyGraphicsResponse,
+    KittyGraphicsStatus, KittyImage, KittyPlacement, Segment,
+};
+
+// The payload of every APC in the output, without the leading G.
+fn commands(sequence: &str) -> Vec<String> {
+    parse(sequence)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::ApplicationProgramCommandSegment(payload) => {
+                String::from(payload.strip_prefix('G').expect("graphics command"))
+            }
+            segment => panic!("unexpected {:?}", segment),
+        })
+        .collect()
+}
+
+#[test]
+fn small_images_are_sent_in_one_command() {
+    let image = KittyImage::rgb(1, 1, vec![255, 0, 128]).unwrap().with_id(7);
+    assert_eq!(
+        KittyGraphicsCommand::TransmitCommand(image).to_string(),
+        "\x1b_Ga=t,f=24,s=1,v=1,i=7;/wCA\x1b\\"
+    );
+    let image = KittyImage::png(b"png".to_vec());
+    assert_eq!(
+        KittyGraphicsCommand::TransmitAndDisplayCommand(image, KittyPlacement::new()).to_string(),
+        "\x1b_Ga=T,f=100;cG5n\x1b\\"
+    );
+}
+
+#[test]
+fn a_payload_of_exactly_one_chunk_is_not_split() {
+    let image = KittyImage::rgba(32, 24, vec![0; 32 * 24 * 4]).unwrap();
+    let commands = commands(&KittyGraphicsCommand::TransmitCommand(image).to_string());
+    assert_eq!(commands.len(), 1);
+    let (keys, payload) = commands[0].split_once(';').unwrap();
+    assert_eq!(keys, "a=t,f=32,s=32,v=24");
+    assert_eq!(payload.len(), 4096);
+}
+
+#[test]
+fn larger_payloads_are_split_into_4096_byte_chunks() {
+    let image = KittyImage::rgb(1, 6145, vec![0; 3 * 6145])
+        .unwrap()
+        .with_id(1);
+    let placement = KittyPlacement::new().with_size(4, 2);
+    let commands =
+        commands(&KittyGraphicsCommand::TransmitAndDisplayCommand(image, placement).to_string());
+    let chunks: Vec<(&str, &str)> = commands
+        .iter()
+        .map(|command| command.split_once(';').unwrap())
+        .collect();
+    assert_eq!(
+        chunks.iter().map(|(keys, _)| *keys).collect::<Vec<&str>>(),
+        vec![
+            "a=T,f=24,s=1,v=6145,i=1,c=4,r=2,m=1",
+            "m=1",
+            "m=1",
+            "m=1",
+            "m=1",
+            "m=1",
+            "m=0"
+        ]
+    );
+    assert!(chunks[..6].iter().all(|(_, payload)| payload.len() == 4096));
+    assert_eq!(chunks[6].1, "AAAA");
+}
+
+#[test]
+fn pixel_data_must_cover_the_image() {
+    assert_eq!(
+        KittyImage::rgb(2, 2, vec![0; 11]),
+        Err(BufferSizeError {
+            expected: 12,
+            actual: 11
+        })
+    );
+    assert_eq!(
+        KittyImage::rgba(2, 2, vec![0; 15]),
+        Err(BufferSizeError {
+            expected: 16,
+            actual: 15
+        })
+    );
+    assert!(KittyImage::rgb(u32::MAX, u32::MAX, vec![0; 3]).is_err());
+    let image = KittyImage::rgba(1, 1, vec![1, 2, 3, 4, 5]).unwrap();
+    assert_eq!(image.data, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn placement_keys_are_written_in_a_fixed_order() {
+    let placement = KittyPlacement::new()
+        .with_z_index(-1)
+        .with_offset(3, 4)
+        .with_size(10, 5)
+        .with_id(2);
+    assert_eq!(
+        KittyGraphicsCommand::DisplayCommand(9, placement).to_string(),
+        "\x1b_Ga=p,i=9,p=2,c=10,r=5,X=3,Y=4,z=-1\x1b\\"
+    );
+    assert_eq!(
+        KittyGraphicsCommand::DisplayCommand(9, KittyPlacement::new()).to_string(),
+        "\x1b_Ga=p,i=9\x1b\\"
+    );
+}
+
+#[test]
+fn deletions_free_the_image_data_with_upper_case_targets() {
+    let cases = [
+        (KittyDeletion::DeleteAllDeletion, false, "a=d,d=a"),
+        (KittyDeletion::DeleteAllDeletion, true, "a=d,d=A"),
+        (KittyDeletion::DeleteImageDeletion(3), false, "a=d,d=i,i=3"),
+        (KittyDeletion::DeleteImageDeletion(3), true, "a=d,d=I,i=3"),
+        (
+            KittyDeletion::DeletePlacementDeletion(3, 4),
+            true,
+            "a=d,d=I,i=3,p=4",
+        ),
+        (KittyDeletion::DeleteAtCursorDeletion, false, "a=d,d=c"),
+        (
+            KittyDeletion::DeleteZIndexDeletion(-2),
+            true,
+            "a=d,d=Z,z=-2",
+        ),
+    ];
+    for (deletion, free_data, keys) in cases.iter() {
+        assert_eq!(
+            KittyGraphicsCommand::DeleteCommand(*deletion, *free_data).to_string(),
+            format!("\x1b_G{}\x1b\\", keys)
+        );
+    }
+}
+
+#[test]
+fn responses_are_parsed() {
+    assert_eq!(
+        KittyGraphicsResponse::parse("\x1b_Gi=31,p=2;OK\x1b\\"),
+        Some(KittyGraphicsResponse {
+            image_id: Some(31),
+            image_number: None,
+            placement_id: Some(2),
+            status: KittyGraphicsStatus::OkStatus,
+        })
+    );
+    assert_eq!(
+        KittyGraphicsResponse::parse("\x1b_GI=5;ENOENT:No such image\x1b\\"),
+        Some(KittyGraphicsResponse {
+            image_id: None,
+            image_number: Some(5),
+            placement_id: None,
+            status: KittyGraphicsStatus::ErrorStatus(
+                String::from("ENOENT"),
+                String::from("No such image")
+            ),
+        })
+    );
+    assert_eq!(KittyGraphicsResponse::parse("\x1b_Xi=1;OK\x1b\\"), None);
+    assert_eq!(KittyGraphicsResponse::parse("\x1b[31m"), None);
+}
@@ -0,0 +1,90 @@
+use ansi_escape_codes::{pad_to_width, slice_columns, truncate_to_width, Alignment};
+
+#[test]
+fn slices_count_columns_not_characters() {
+    let cases = [
+        ("abcdef", 1, 4, "bcd"),
+        ("abc", 1, 10, "bc"),
+        ("abc", 5, 10, ""),
+        ("e\u{301}xyz", 0, 2, "e\u{301}x"),
+        ("\u{301}abc", 0, 2, "\u{301}ab"),
+        ("😀👍\u{1f3fd}x", 2, 5, "👍\u{1f3fd}x"),
+    ];
+    for (text, start, end, expected) in cases.iter() {
+        assert_eq!(
+            slice_columns(text, *start, *end),
+            *expected,
+            "{:?} {}..{}",
+            text,
+            start,
+            end
+        );
+    }
+}
+
+#[test]
+fn wide_characters_cut_by_an_edge_become_spaces() {
+    assert_eq!(slice_columns("漢字", 1, 4), " 字");
+    assert_eq!(slice_columns("漢字", 0, 3), "漢 ");
+    assert_eq!(slice_columns("漢字漢", 1, 5), " 字 ");
+    assert_eq!(slice_columns("漢", 1, 2), " ");
+}
+
+#[test]
+fn styles_active_across_the_cut_are_reopened_and_closed() {
+    assert_eq!(
+        slice_columns("\x1b[1;31mred\x1b[0m", 1, 2),
+        "\x1b[0;1;31me\x1b[0m"
+    );
+    assert_eq!(
+        slice_columns("\x1b[1mbold\x1b[0m plain", 2, 7),
+        "\x1b[0;1mld\x1b[0m pl"
+    );
+    // Sequences after the end of the slice are left out.
+    assert_eq!(slice_columns("ab\x1b[31mcd", 0, 2), "ab");
+}
+
+#[test]
+fn links_active_across_the_cut_are_reopened_and_closed() {
+    let text = "\x1b[31mab\x1b]8;id=1;http://x\x1b\\link\x1b]8;;\x1b\\\x1b[0m";
+    assert_eq!(
+        slice_columns(text, 3, 5),
+        "\x1b[0;31m\x1b]8;id=1;http://x\x1b\\in\x1b[0m\x1b]8;;\x1b\\"
+    );
+    assert_eq!(
+        slice_columns(text, 0, 3),
+        "\x1b[31mab\x1b]8;id=1;http://x\x1b\\l\x1b[0m\x1b]8;;\x1b\\"
+    );
+}
+
+#[test]
+fn truncation_ends_with_the_ellipsis_in_the_active_style() {
+    assert_eq!(truncate_to_width("abcdef", 6, "…"), "abcdef");
+    assert_eq!(truncate_to_width("abcdef", 4, "…"), "abc…");
+    assert_eq!(truncate_to_width("abcdef", 4, "..."), "a...");
+    assert_eq!(
+        truncate_to_width("\x1b[1mabcdef", 4, "…"),
+        "\x1b[1mabc…\x1b[0m"
+    );
+    assert_eq!(truncate_to_width("漢字漢", 4, "…"), "漢 …");
+}
+
+#[test]
+fn an_ellipsis_wider_than_the_width_is_left_out() {
+    assert_eq!(truncate_to_width("abcdef", 2, "..."), "ab");
+    assert_eq!(truncate_to_width("abcdef", 0, "…"), "");
+    assert_eq!(truncate_to_width("漢字", 1, "……"), " ");
+}
+
+#[test]
+fn padding_goes_where_the_alignment_says() {
+    assert_eq!(pad_to_width("ab", 5, Alignment::LeftAlignment), "ab   ");
+    assert_eq!(pad_to_width("ab", 5, Alignment::RightAlignment), "   ab");
+    assert_eq!(pad_to_width("ab", 5, Alignment::CenterAlignment), " ab  ");
+    assert_eq!(pad_to_width("漢", 3, Alignment::RightAlignment), " 漢");
+    assert_eq!(pad_to_width("abc", 2, Alignment::LeftAlignment), "abc");
+    assert_eq!(
+        pad_to_width("\x1b[31mab", 3, Alignment::LeftAlignment),
+        "\x1b[31mab\x1b[0m "
+    );
+}
@@ -0,0 +1,368 @@
+use ansi_escape_codes::{
+    parse_color_spec, Color, EscapeSequence, Hyperlink, ImageDimension, InlineImage,
+    OperatingSystemCommandParameter, SelectionTarget,
+};
+
+#[test]
+fn links_open_and_close_around_their_text() {
+    assert_eq!(
+        Hyperlink::new("https://example.com")
+            .link("site")
+            .to_string(),
+        "\x1b]8;;https://example.com\x1b\\site\x1b]8;;\x1b\\"
+    );
+    assert_eq!(
+        Hyperlink::new("https://example.com/a b")
+            .with_id("x1")
+            .link("site")
+            .to_string(),
+        "\x1b]8;id=x1;https://example.com/a%20b\x1b\\site\x1b]8;;\x1b\\"
+    );
+}
+
+#[test]
+fn link_ids_cannot_break_out_of_their_parameter() {
+    let hyperlink = Hyperlink::new("https://example.com").with_id("a:b;c%d");
+    let sequence = EscapeSequence::OperatingSystemCommandSequence(
+        OperatingSystemCommandParameter::HyperlinkStartParameter(hyperlink.clone()),
+    )
+    .to_string();
+    assert_eq!(
+        sequence,
+        "\x1b]8;id=a%3Ab%3Bc%25d;https://example.com\x1b\\"
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse_sequence(&sequence),
+        Some(OperatingSystemCommandParameter::HyperlinkStartParameter(
+            hyperlink
+        ))
+    );
+}
+
+#[test]
+fn links_are_parsed_from_their_payload() {
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("8;foo=bar:id=7;file:///tmp"),
+        Some(OperatingSystemCommandParameter::HyperlinkStartParameter(
+            Hyperlink::new("file:///tmp").with_id("7")
+        ))
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("8;;"),
+        Some(OperatingSystemCommandParameter::HyperlinkEndParameter)
+    );
+    assert_eq!(OperatingSystemCommandParameter::parse("8"), None);
+}
+
+#[test]
+fn titles_are_written_without_control_characters() {
+    let cases = [
+        (
+            OperatingSystemCommandParameter::SetIconNameAndWindowTitleParameter(String::from(
+                "both\x1b]2;x\x07",
+            )),
+            "\x1b]0;both]2;x\x1b\\",
+        ),
+        (
+            OperatingSystemCommandParameter::SetIconNameParameter(String::from("icon")),
+            "\x1b]1;icon\x1b\\",
+        ),
+        (
+            OperatingSystemCommandParameter::SetWindowTitleParameter(String::from("línea\n2")),
+            "\x1b]2;línea2\x1b\\",
+        ),
+    ];
+    for (parameter, expected) in cases.iter() {
+        assert_eq!(
+            EscapeSequence::OperatingSystemCommandSequence(parameter.clone()).to_string(),
+            *expected
+        );
+    }
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("2;a;b"),
+        Some(OperatingSystemCommandParameter::SetWindowTitleParameter(
+            String::from("a;b")
+        ))
+    );
+}
+
+#[test]
+fn titles_are_pushed_and_popped() {
+    assert_eq!(EscapeSequence::PushTitleSequence.to_string(), "\x1b[22;0t");
+    assert_eq!(EscapeSequence::PopTitleSequence.to_string(), "\x1b[23;0t");
+}
+
+#[test]
+fn working_directories_are_percent_encoded() {
+    let directory = OperatingSystemCommandParameter::CurrentWorkingDirectoryParameter(
+        String::from("my host"),
+        String::from("/home/me/My Files/café/a-b_c.d~%"),
+    );
+    let payload = directory.to_string();
+    assert_eq!(
+        payload,
+        "7;file://my%20host/home/me/My%20Files/caf%C3%A9/a-b_c.d~%25"
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse(&payload),
+        Some(directory)
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("7;file://host"),
+        Some(
+            OperatingSystemCommandParameter::CurrentWorkingDirectoryParameter(
+                String::from("host"),
+                String::new()
+            )
+        )
+    );
+    assert_eq!(OperatingSystemCommandParameter::parse("7;/tmp"), None);
+}
+
+#[test]
+fn inline_images_are_written_with_their_arguments() {
+    let image = InlineImage::new(b"png".to_vec())
+        .with_name("a.png")
+        .with_width(ImageDimension::CellsDimension(10))
+        .with_height(ImageDimension::PercentDimension(50));
+    let parameter = OperatingSystemCommandParameter::InlineImageParameter(image);
+    let payload = parameter.to_string();
+    assert_eq!(
+        payload,
+        "1337;File=name=YS5wbmc=;size=3;width=10;height=50%;preserveAspectRatio=1;inline=1:cG5n"
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse(&payload),
+        Some(parameter)
+    );
+
+    let download = InlineImage::new(vec![0, 255])
+        .with_width(ImageDimension::PixelsDimension(64))
+        .with_preserve_aspect_ratio(false)
+        .with_inline(false);
+    let parameter = OperatingSystemCommandParameter::InlineImageParameter(download);
+    let payload = parameter.to_string();
+    assert_eq!(
+        payload,
+        "1337;File=size=2;width=64px;height=auto;preserveAspectRatio=0;inline=0:AP8="
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse(&payload),
+        Some(parameter)
+    );
+}
+
+#[test]
+fn inline_images_are_downloads_unless_marked_inline() {
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("1337;File=size=3:cG5n"),
+        Some(OperatingSystemCommandParameter::InlineImageParameter(
+            InlineImage::new(b"png".to_vec()).with_inline(false)
+        ))
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("1337;File=width=wide;inline=1:cG5n"),
+        None
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("1337;File=inline=1:not base64"),
+        None
+    );
+}
+
+#[test]
+fn iterm2_marks_directories_and_user_variables_round_trip() {
+    let cases = [
+        (
+            OperatingSystemCommandParameter::SetMarkParameter,
+            "1337;SetMark",
+        ),
+        (
+            OperatingSystemCommandParameter::CurrentDirectoryParameter(String::from("/tmp/a b")),
+            "1337;CurrentDir=/tmp/a b",
+        ),
+        (
+            OperatingSystemCommandParameter::SetUserVariableParameter(
+                String::from("git_branch"),
+                String::from("main"),
+            ),
+            "1337;SetUserVar=git_branch=bWFpbg==",
+        ),
+    ];
+    for (parameter, payload) in cases.iter() {
+        assert_eq!(parameter.to_string(), *payload);
+        assert_eq!(
+            OperatingSystemCommandParameter::parse(payload).as_ref(),
+            Some(parameter)
+        );
+    }
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("1337;SetUserVar=name"),
+        None
+    );
+    assert_eq!(OperatingSystemCommandParameter::parse("1337;Unknown"), None);
+}
+
+fn clipboard(payload: &str) -> Option<OperatingSystemCommandParameter> {
+    OperatingSystemCommandParameter::parse(payload)
+}
+
+#[test]
+fn selection_targets_use_xterms_letters() {
+    let targets = vec![
+        SelectionTarget::ClipboardTarget,
+        SelectionTarget::PrimarySelectionTarget,
+        SelectionTarget::SecondarySelectionTarget,
+        SelectionTarget::SelectSelectionTarget,
+    ];
+    assert_eq!(
+        OperatingSystemCommandParameter::QueryClipboardParameter(targets.clone()).to_string(),
+        "52;cpqs;?"
+    );
+    assert_eq!(
+        clipboard("52;cpqs;?"),
+        Some(OperatingSystemCommandParameter::QueryClipboardParameter(
+            targets
+        ))
+    );
+}
+
+#[test]
+fn clipboard_text_is_base64_encoded_with_padding() {
+    let cases = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+        ("ü?>", "w7w/Pg=="),
+    ];
+    for (text, encoded) in cases.iter() {
+        let parameter = OperatingSystemCommandParameter::SetClipboardParameter(
+            vec![SelectionTarget::ClipboardTarget],
+            String::from(*text),
+        );
+        let sequence = EscapeSequence::OperatingSystemCommandSequence(parameter.clone());
+        assert_eq!(sequence.to_string(), format!("\x1b]52;c;{}\x1b\\", encoded));
+        assert_eq!(
+            OperatingSystemCommandParameter::parse_sequence(&sequence.to_string()),
+            Some(parameter)
+        );
+    }
+}
+
+#[test]
+fn clipboard_replies_may_leave_out_padding() {
+    assert_eq!(
+        clipboard("52;p;Zm9vYg"),
+        Some(OperatingSystemCommandParameter::SetClipboardParameter(
+            vec![SelectionTarget::PrimarySelectionTarget],
+            String::from("foob")
+        ))
+    );
+}
+
+#[test]
+fn invalid_clipboard_replies_are_rejected() {
+    // Outside the alphabet, padding in the middle, and bytes that are not UTF-8.
+    assert_eq!(clipboard("52;c;Zm9v!"), None);
+    assert_eq!(clipboard("52;c;Zg==Zg=="), None);
+    assert_eq!(clipboard("52;c;/w=="), None);
+    assert_eq!(clipboard("52;c"), None);
+}
+
+#[test]
+fn color_specs_are_scaled_to_8_bits() {
+    let cases = [
+        ("rgb:f/8/0", (255, 136, 0)),
+        ("rgb:ff/80/00", (255, 128, 0)),
+        ("rgb:fff/800/000", (255, 128, 0)),
+        ("rgb:ffff/8000/0000", (255, 128, 0)),
+        ("rgb:FFFF/8/00", (255, 136, 0)),
+        ("#f80", (240, 128, 0)),
+        ("#ff8800", (255, 136, 0)),
+        ("#fff888000", (255, 136, 0)),
+        ("#ffff88880000", (255, 136, 0)),
+    ];
+    for (spec, (red, green, blue)) in cases.iter() {
+        assert_eq!(
+            parse_color_spec(spec),
+            Some(Color::RGB(*red, *green, *blue)),
+            "{}",
+            spec
+        );
+    }
+}
+
+#[test]
+fn invalid_color_specs_are_rejected() {
+    let cases = [
+        "",
+        "red",
+        "rgb:",
+        "rgb:f/f",
+        "rgb:f/f/f/f",
+        "rgb:f//f",
+        "rgb:fffff/0/0",
+        "rgb:g/0/0",
+        "rgb:+f/0/0",
+        "#",
+        "#ff",
+        "#fffff",
+        "#ff88000000000",
+        "#+f+f+f",
+        "#ééé",
+    ];
+    for spec in cases.iter() {
+        assert_eq!(parse_color_spec(spec), None, "{}", spec);
+    }
+}
+
+#[test]
+fn palette_colors_resolve_to_xterms_defaults() {
+    let cases = [
+        (1, (205, 0, 0)),
+        (12, (92, 92, 255)),
+        (16, (0, 0, 0)),
+        (21, (0, 0, 255)),
+        (110, (135, 175, 215)),
+        (196, (255, 0, 0)),
+        (231, (255, 255, 255)),
+        (232, (8, 8, 8)),
+        (255, (238, 238, 238)),
+    ];
+    for (color_code, rgb) in cases.iter() {
+        assert_eq!(Color::Color256(*color_code).to_rgb(), *rgb);
+    }
+    assert_eq!(Color::RGB(1, 2, 3).to_rgb(), (1, 2, 3));
+}
+
+#[test]
+fn color_queries_and_replies_round_trip() {
+    let reply =
+        OperatingSystemCommandParameter::SetDefaultBackgroundColorParameter(Color::Color256(196))
+            .to_string();
+    assert_eq!(reply, "11;rgb:ff/00/00");
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("11;rgb:ffff/0000/0000"),
+        Some(
+            OperatingSystemCommandParameter::SetDefaultBackgroundColorParameter(Color::RGB(
+                255, 0, 0
+            ))
+        )
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("4;12;?"),
+        Some(OperatingSystemCommandParameter::QueryPaletteColorParameter(
+            12
+        ))
+    );
+    assert_eq!(
+        OperatingSystemCommandParameter::parse("104"),
+        Some(OperatingSystemCommandParameter::ResetPaletteColorParameter(
+            None
+        ))
+    );
+}
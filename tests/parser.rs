@@ -0,0 +1,103 @@
+use ansi_escape_codes::parser::parse;
+use ansi_escape_codes::{ControlSequence, Segment};
+
+fn text(text: &str) -> Segment {
+    Segment::TextSegment(String::from(text))
+}
+
+fn control_sequence(parameters: &str, intermediates: &str, final_byte: char) -> Segment {
+    Segment::ControlSequenceSegment(ControlSequence {
+        parameters: String::from(parameters),
+        intermediates: String::from(intermediates),
+        final_byte,
+    })
+}
+
+#[test]
+fn text_and_sequences_are_split_apart() {
+    assert_eq!(
+        parse("a\x1b[1;31mb\x1b[0 qc\r\n"),
+        vec![
+            text("a"),
+            control_sequence("1;31", "", 'm'),
+            text("b"),
+            control_sequence("0", " ", 'q'),
+            text("c"),
+            Segment::ControlCodeSegment('\r'),
+            Segment::ControlCodeSegment('\n'),
+        ]
+    );
+}
+
+#[test]
+fn c1_introducers_are_recognised() {
+    assert_eq!(
+        parse("\u{9b}2J\u{9d}0;title\u{9c}\u{90}$q\"p\u{9c}\u{9f}apc\u{9c}"),
+        vec![
+            control_sequence("2", "", 'J'),
+            Segment::OperatingSystemCommandSegment(String::from("0;title")),
+            Segment::DeviceControlStringSegment(String::from("$q\"p")),
+            Segment::ApplicationProgramCommandSegment(String::from("apc")),
+        ]
+    );
+    assert_eq!(
+        parse("\u{98}sos\u{9c}\u{9e}pm\u{9c}"),
+        vec![
+            Segment::StartOfStringSegment(String::from("sos")),
+            Segment::PrivacyMessageSegment(String::from("pm")),
+        ]
+    );
+}
+
+#[test]
+fn control_strings_end_at_bel_or_st() {
+    let title = Segment::OperatingSystemCommandSegment(String::from("2;title"));
+    assert_eq!(parse("\x1b]2;title\x07"), vec![title.clone()]);
+    assert_eq!(parse("\x1b]2;title\x1b\\"), vec![title.clone()]);
+    assert_eq!(parse("\x1b]2;title\u{9c}x"), vec![title, text("x")]);
+}
+
+#[test]
+fn unterminated_sequences_fall_back_to_a_bare_escape() {
+    assert_eq!(
+        parse("\x1b]2;title"),
+        vec![Segment::ControlCodeSegment('\x1b'), text("]2;title")]
+    );
+    // An escape inside a control string that is not part of ST cuts it short.
+    assert_eq!(
+        parse("\x1b]2;a\x1b[mb"),
+        vec![
+            Segment::ControlCodeSegment('\x1b'),
+            text("]2;a"),
+            control_sequence("", "", 'm'),
+            text("b"),
+        ]
+    );
+    assert_eq!(
+        parse("\x1b[12"),
+        vec![Segment::ControlCodeSegment('\x1b'), text("[12")]
+    );
+    assert_eq!(parse("\x1b"), vec![Segment::ControlCodeSegment('\x1b')]);
+}
+
+#[test]
+fn other_escapes_are_kept_whole() {
+    assert_eq!(
+        parse("\x1b7\x1b(0x\x1b8"),
+        vec![
+            Segment::EscapeSegment(String::from("7")),
+            Segment::EscapeSegment(String::from("(0")),
+            text("x"),
+            Segment::EscapeSegment(String::from("8")),
+        ]
+    );
+}
+
+#[test]
+fn segments_are_written_back_in_their_7_bit_form() {
+    let written: String = parse("\u{9b}1m\u{9d}2;t\x07x\x1b7")
+        .iter()
+        .map(|segment| segment.to_string())
+        .collect();
+    assert_eq!(written, "\x1b[1m\x1b]2;t\x1b\\x\x1b7");
+}
@@ -0,0 +1,46 @@
+use ansi_escape_codes::{Color, DiffRenderer, Screen, Style};
+
+#[test]
+fn only_changed_cells_are_written() {
+    let mut renderer = DiffRenderer::new(20, 3).with_synchronized_output(true);
+    let mut red = Style::new();
+    red.foreground = Some(Color::Color256(1));
+    renderer.print(0, 0, "status: ok", Style::new());
+    renderer.print(2, 0, "--------", Style::new());
+    assert_eq!(
+        renderer.render(),
+        "\x1b[?2026h\x1b[0m\x1b[H\x1b[2Jstatus: ok\x1b[3H-\x1b[7b\x1b[?2026l"
+    );
+    assert_eq!(renderer.render(), "");
+    renderer.print(0, 8, "no", red);
+    assert_eq!(renderer.render(), "\x1b[?2026h\x1b[2A\x1b[31mno\x1b[?2026l");
+}
+
+#[test]
+fn the_output_reproduces_the_frame() {
+    let mut renderer = DiffRenderer::new(12, 4);
+    let mut screen = Screen::new(12, 4);
+    let mut bold = Style::new();
+    bold.bold = true;
+    let frames: [&[(usize, usize, &str, Style)]; 3] = [
+        &[
+            (0, 0, "hello world", bold),
+            (3, 2, "aaaaaaaa", Style::new()),
+        ],
+        &[(0, 6, "there", Style::new()), (1, 11, "x", bold)],
+        &[(3, 0, "bbbbbbbbbbbb", bold), (2, 3, "   z", Style::new())],
+    ];
+    for frame in frames.iter() {
+        for &(row, column, text, style) in frame.iter() {
+            renderer.print(row, column, text, style);
+        }
+        renderer.set_cursor(Some((1, 0)));
+        screen.write(&renderer.render());
+        for row in 0..4 {
+            for column in 0..12 {
+                assert_eq!(renderer.cell(row, column), screen.cell(row, column));
+            }
+        }
+        assert_eq!(screen.cursor(), (1, 0));
+    }
+}
@@ -0,0 +1,63 @@
+use ansi_escape_codes::{ReplacementNotation, SanitizePolicy, SequenceCategory};
+
+#[test]
+fn the_default_policy_keeps_only_text_newlines_and_tabs() {
+    let policy = SanitizePolicy::new();
+    assert_eq!(
+        policy.sanitize("\x1b[31mred\x1b[0m\tcell\r\nnext\x07"),
+        "red\tcell\nnext"
+    );
+}
+
+#[test]
+fn styled_text_keeps_colors_and_links_but_not_terminal_changes() {
+    let policy = SanitizePolicy::styled_text();
+    let input = concat!(
+        "\x1b[1;38;5;208mwarn\x1b[0m ",
+        "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ ",
+        "\x1b[2J\x1b[H\x1b]0;pwned\x07\x1b]52;c;aGk=\x07\x1bP$qm\x1b\\\x1b[6n\x1b[?1049h\x1b[>4;2m"
+    );
+    assert_eq!(
+        policy.sanitize(input),
+        "\x1b[1;38;5;208mwarn\x1b[0m \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ "
+    );
+}
+
+#[test]
+fn categories_can_be_allowed_individually() {
+    let policy = SanitizePolicy::new()
+        .with_category(SequenceCategory::CursorMovementCategory)
+        .with_category(SequenceCategory::WindowTitleCategory)
+        .with_control_code('\r');
+    assert_eq!(
+        policy.sanitize("\x1b[2A\x1b[K\r\x1b]2;build\x1b\\\x1b]52;c;aGk=\x1b\\"),
+        "\x1b[2A\r\x1b]2;build\x1b\\"
+    );
+}
+
+#[test]
+fn c1_introducers_are_re_emitted_in_their_7_bit_form() {
+    let policy = SanitizePolicy::styled_text();
+    assert_eq!(policy.sanitize("\u{9b}32mok"), "\x1b[32mok");
+    assert_eq!(policy.sanitize("\u{85}x"), "x");
+}
+
+#[test]
+fn control_strings_with_embedded_control_codes_are_rejected() {
+    let policy = SanitizePolicy::styled_text();
+    assert_eq!(
+        policy.sanitize("\x1b]8;;https://example.com/\u{9b}2J\x1b\\text"),
+        "text"
+    );
+}
+
+#[test]
+fn disallowed_input_can_be_made_visible() {
+    let caret = SanitizePolicy::new().with_notation(ReplacementNotation::CaretNotation);
+    assert_eq!(caret.sanitize("a\x1b[2Jb\rc\x7f\u{9b}"), "a^[[2Jb^Mc^?M-^[");
+    let pictures = SanitizePolicy::new().with_notation(ReplacementNotation::ControlPictureNotation);
+    assert_eq!(
+        pictures.sanitize("a\x1b]0;t\x07b\x00"),
+        "a\u{241b}]0;t\u{241b}\\b\u{2400}"
+    );
+}
@@ -0,0 +1,180 @@
+use ansi_escape_codes::{Color, Screen};
+
+fn screen(width: usize, height: usize, output: &str) -> Screen {
+    let mut screen = Screen::new(width, height);
+    screen.write(output);
+    screen
+}
+
+fn rows(screen: &Screen) -> Vec<String> {
+    (0..screen.height())
+        .map(|row| screen.row_text(row))
+        .collect()
+}
+
+#[test]
+fn the_cursor_moves_and_stops_at_the_edges() {
+    let cases = [
+        ("\x1b[3;5H", (2, 4)),
+        ("\x1b[3;5H\x1b[A\x1b[2D", (1, 2)),
+        ("\x1b[3;5H\x1b[B\x1b[C", (3, 5)),
+        ("\x1b[99A\x1b[99D", (0, 0)),
+        ("\x1b[99B\x1b[99C", (4, 9)),
+        ("\x1b[3;5H\x1b[E", (3, 0)),
+        ("\x1b[3;5H\x1b[2F", (0, 0)),
+        ("\x1b[7G\x1b[2d", (1, 6)),
+        ("\x1b[0;0H", (0, 0)),
+        ("ab\x08\x08\x08", (0, 0)),
+        ("a\tb\t", (0, 9)),
+        ("\x1b[2;3H\x1b7\x1b[H\x1b8", (1, 2)),
+        ("\x1b[2;3H\x1b[s\x1b[H\x1b[u", (1, 2)),
+    ];
+    for (output, cursor) in cases.iter() {
+        assert_eq!(screen(10, 5, output).cursor(), *cursor, "{:?}", output);
+    }
+}
+
+#[test]
+fn huge_counts_do_not_overflow() {
+    let max = usize::MAX;
+    for final_byte in [
+        "B", "C", "E", "a", "e", "X", "@", "P", "L", "M", "S", "T", "b",
+    ]
+    .iter()
+    {
+        let mut screen = screen(10, 5, "x\x1b[2;2H");
+        screen.write(&format!("\x1b[{}{}", max, final_byte));
+        screen.write(&format!("\x1b[{};{}H", max, max));
+    }
+    assert_eq!(screen(10, 5, &format!("\x1b[{}B", max)).cursor(), (4, 0));
+    assert_eq!(screen(10, 5, &format!("\x1b[{}C", max)).cursor(), (0, 9));
+}
+
+#[test]
+fn erasing_keeps_the_current_background() {
+    let fill = ["0123456789"; 3].join("\r\n");
+    let cases = [
+        ("\x1b[2;4H\x1b[K", vec!["0123456789", "012", "0123456789"]),
+        (
+            "\x1b[2;4H\x1b[1K",
+            vec!["0123456789", "    456789", "0123456789"],
+        ),
+        ("\x1b[2;4H\x1b[2K", vec!["0123456789", "", "0123456789"]),
+        ("\x1b[2;4H\x1b[J", vec!["0123456789", "012", ""]),
+        ("\x1b[2;4H\x1b[1J", vec!["", "    456789", "0123456789"]),
+        (
+            "\x1b[2;4H\x1b[3X",
+            vec!["0123456789", "012   6789", "0123456789"],
+        ),
+        (
+            "\x1b[2;4H\x1b[2@",
+            vec!["0123456789", "012  34567", "0123456789"],
+        ),
+        (
+            "\x1b[2;4H\x1b[2P",
+            vec!["0123456789", "01256789", "0123456789"],
+        ),
+    ];
+    for (output, expected) in cases.iter() {
+        let screen = screen(10, 3, &format!("{}{}", fill, output));
+        assert_eq!(rows(&screen), *expected, "{:?}", output);
+    }
+    let screen = screen(4, 1, "abcd\x1b[44m\x1b[2;3H\x1b[1;2H\x1b[K");
+    assert_eq!(screen.row_text(0), "a");
+    let cell = screen.cell(0, 3).unwrap();
+    assert_eq!(cell.style.background, Some(Color::Color256(4)));
+    assert!(!cell.style.bold);
+}
+
+#[test]
+fn text_wraps_at_the_right_edge() {
+    let screen = screen(4, 3, "abcdef");
+    assert_eq!(rows(&screen), vec!["abcd", "ef", ""]);
+    assert_eq!(screen.cursor(), (1, 2));
+    // Writing the last column leaves the cursor there until the next character.
+    let screen = self::screen(4, 3, "abcd");
+    assert_eq!(screen.cursor(), (0, 3));
+    let screen = self::screen(4, 3, "abcd\r\n");
+    assert_eq!(rows(&screen), vec!["abcd", "", ""]);
+    assert_eq!(screen.cursor(), (1, 0));
+}
+
+#[test]
+fn the_screen_scrolls_at_the_bottom() {
+    let screen = screen(3, 2, "a\r\nb\r\nc\r\nd");
+    assert_eq!(rows(&screen), vec!["c", "d"]);
+    let screen = self::screen(3, 3, "a\r\nb\r\nc\x1b[S");
+    assert_eq!(rows(&screen), vec!["b", "c", ""]);
+    let screen = self::screen(3, 3, "a\r\nb\r\nc\x1b[2T");
+    assert_eq!(rows(&screen), vec!["", "", "a"]);
+    let screen = self::screen(3, 3, "a\r\nb\r\nc\x1bM\x1b[H\x1bM");
+    assert_eq!(rows(&screen), vec!["", "a", "b"]);
+}
+
+#[test]
+fn scrolling_stays_inside_the_margins() {
+    let fill = "a\r\nb\r\nc\r\nd\r\ne";
+    let screen = screen(3, 5, &format!("{}\x1b[2;4r\x1b[4H\nx", fill));
+    assert_eq!(rows(&screen), vec!["a", "c", "d", "x", "e"]);
+    let screen = self::screen(3, 5, &format!("{}\x1b[2;4r\x1b[2H\x1b[L", fill));
+    assert_eq!(rows(&screen), vec!["a", "", "b", "c", "e"]);
+    let screen = self::screen(3, 5, &format!("{}\x1b[2;4r\x1b[3H\x1b[M", fill));
+    assert_eq!(rows(&screen), vec!["a", "b", "d", "", "e"]);
+    // Outside the margins lines are neither inserted nor deleted.
+    let screen = self::screen(3, 5, &format!("{}\x1b[2;4r\x1b[5H\x1b[L", fill));
+    assert_eq!(rows(&screen), vec!["a", "b", "c", "d", "e"]);
+    // An invalid region is ignored.
+    let screen = self::screen(3, 3, "a\x1b[3;2r\r\n\n\nb");
+    assert_eq!(rows(&screen), vec!["", "", "b"]);
+}
+
+#[test]
+fn wide_characters_take_two_cells() {
+    let screen = screen(6, 2, "a漢b");
+    assert_eq!(screen.row_text(0), "a漢b");
+    assert_eq!(screen.cursor(), (0, 4));
+    assert_eq!(screen.text(0, 1), Some(String::from("漢")));
+    assert_eq!(screen.text(0, 2), Some(String::new()));
+    // Only one column left: the character moves to the next line.
+    let screen = self::screen(6, 2, "abcde漢");
+    assert_eq!(rows(&screen), vec!["abcde", "漢"]);
+    assert_eq!(screen.cursor(), (1, 2));
+}
+
+#[test]
+fn overwriting_half_of_a_wide_character_blanks_the_other_half() {
+    let screen = screen(6, 1, "漢字\x1b[2Gx");
+    assert_eq!(screen.row_text(0), " x字");
+    let screen = self::screen(6, 1, "漢字\x1b[3Gx");
+    assert_eq!(screen.row_text(0), "漢x");
+    let screen = self::screen(6, 1, "漢字\x1b[2G\x1b[X");
+    assert_eq!(screen.row_text(0), "  字");
+    let screen = self::screen(6, 1, "a漢字\x1b[2G\x1b[2@");
+    assert_eq!(screen.row_text(0), "a  漢");
+    let screen = self::screen(6, 1, "a漢字\x1b[2G\x1b[P");
+    assert_eq!(screen.row_text(0), "a 字");
+}
+
+#[test]
+fn zero_width_characters_join_the_previous_cell() {
+    let screen = screen(6, 1, "e\u{301}x\u{fe0f}");
+    assert_eq!(screen.row_text(0), "e\u{301}x\u{fe0f}");
+    assert_eq!(screen.cursor(), (0, 2));
+    assert_eq!(screen.text(0, 0), Some(String::from("e\u{301}")));
+    let screen = self::screen(6, 1, "漢\u{301}");
+    assert_eq!(screen.text(0, 0), Some(String::from("漢\u{301}")));
+    // At the start of a line there is nothing to attach to.
+    let screen = self::screen(6, 1, "\u{301}a");
+    assert_eq!(screen.row_text(0), "a");
+    // Overwriting the cell drops its marks.
+    let screen = self::screen(6, 1, "e\u{301}\rx");
+    assert_eq!(screen.row_text(0), "x");
+}
+
+#[test]
+fn rep_repeats_the_last_character() {
+    let screen = screen(10, 2, "ab\x1b[3b\x1b[b");
+    assert_eq!(screen.row_text(0), "abbbbb");
+    let screen = self::screen(4, 2, "漢\x1b[2b");
+    assert_eq!(rows(&screen), vec!["漢漢", "漢"]);
+}
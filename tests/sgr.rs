@@ -0,0 +1,420 @@
+use ansi_escape_codes::{
+    BackgroundColor, BrightBackgroundColor, BrightForegroundColor, Color, EscapeSequence,
+    ForegroundColor, SelectGraphicRenditionParameter,
+};
+
+// Codes as documented in ECMA-48 section 8.3.117, with the xterm/aixterm bright colors,
+// kitty's underline styles and colors and mintty's superscript/subscript.
+fn sgr_table() -> Vec<(SelectGraphicRenditionParameter, &'static str)> {
+    vec![
+        (SelectGraphicRenditionParameter::ResetNormalParameter, "0"),
+        (SelectGraphicRenditionParameter::BoldParameter, "1"),
+        (
+            SelectGraphicRenditionParameter::IncreasedSensitivityParameter,
+            "1",
+        ),
+        (SelectGraphicRenditionParameter::FaintParameter, "2"),
+        (
+            SelectGraphicRenditionParameter::DecreasedSensitivityParameter,
+            "2",
+        ),
+        (SelectGraphicRenditionParameter::ItalicParameter, "3"),
+        (SelectGraphicRenditionParameter::UnderlineParameter, "4"),
+        (
+            SelectGraphicRenditionParameter::UnderlineStyleOffParameter,
+            "4:0",
+        ),
+        (
+            SelectGraphicRenditionParameter::CurlyUnderlineParameter,
+            "4:3",
+        ),
+        (
+            SelectGraphicRenditionParameter::DottedUnderlineParameter,
+            "4:4",
+        ),
+        (
+            SelectGraphicRenditionParameter::DashedUnderlineParameter,
+            "4:5",
+        ),
+        (SelectGraphicRenditionParameter::SlowBlinkParameter, "5"),
+        (SelectGraphicRenditionParameter::RapidBlinkParameter, "6"),
+        (SelectGraphicRenditionParameter::ReverseVideoParameter, "7"),
+        (SelectGraphicRenditionParameter::ConcealParameter, "8"),
+        (SelectGraphicRenditionParameter::CrossedOutParameter, "9"),
+        (SelectGraphicRenditionParameter::PrimaryFontParameter, "10"),
+        (SelectGraphicRenditionParameter::DefaultFontParameter, "10"),
+        (
+            SelectGraphicRenditionParameter::AlternativeFontParameter(11),
+            "11",
+        ),
+        (
+            SelectGraphicRenditionParameter::AlternativeFontParameter(19),
+            "19",
+        ),
+        (
+            SelectGraphicRenditionParameter::AlternativeFontParameter(3),
+            "11",
+        ),
+        (
+            SelectGraphicRenditionParameter::AlternativeFontParameter(42),
+            "19",
+        ),
+        (SelectGraphicRenditionParameter::FrakturParameter, "20"),
+        (
+            SelectGraphicRenditionParameter::DoubleUnderlineParameter,
+            "21",
+        ),
+        (SelectGraphicRenditionParameter::BoldOffParameter, "22"),
+        (SelectGraphicRenditionParameter::NormalColorParameter, "22"),
+        (
+            SelectGraphicRenditionParameter::NormalIntensityParameter,
+            "22",
+        ),
+        (SelectGraphicRenditionParameter::NotItalicParameter, "23"),
+        (SelectGraphicRenditionParameter::NotFrakturParameter, "23"),
+        (SelectGraphicRenditionParameter::UnderlineOffParameter, "24"),
+        (SelectGraphicRenditionParameter::BlinkOffParameter, "25"),
+        (
+            SelectGraphicRenditionParameter::ProportionalSpacingParameter,
+            "26",
+        ),
+        (SelectGraphicRenditionParameter::InverseOffParameter, "27"),
+        (SelectGraphicRenditionParameter::RevealParameter, "28"),
+        (
+            SelectGraphicRenditionParameter::NotCrossedOutParameter,
+            "29",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::BlackForeground,
+            ),
+            "30",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::RedForeground,
+            ),
+            "31",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::GreenForeground,
+            ),
+            "32",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::YellowForeground,
+            ),
+            "33",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::BlueForeground,
+            ),
+            "34",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::MagentaForeground,
+            ),
+            "35",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::CyanForeground,
+            ),
+            "36",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::WhiteForeground,
+            ),
+            "37",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::ForegroundColor(Color::Color256(208)),
+            ),
+            "38;5;208",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetForegroundColorParameter(
+                ForegroundColor::ForegroundColor(Color::RGB(1, 2, 3)),
+            ),
+            "38;2;1;2;3",
+        ),
+        (
+            SelectGraphicRenditionParameter::DefaultForegroundColorParameter,
+            "39",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::BlackBackground,
+            ),
+            "40",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::RedBackground,
+            ),
+            "41",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::GreenBackground,
+            ),
+            "42",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::YellowBackground,
+            ),
+            "43",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::BlueBackground,
+            ),
+            "44",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::MagentaBackground,
+            ),
+            "45",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::CyanBackground,
+            ),
+            "46",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::WhiteBackground,
+            ),
+            "47",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::BackgroundColor(Color::Color256(17)),
+            ),
+            "48;5;17",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBackgroundColorParameter(
+                BackgroundColor::BackgroundColor(Color::RGB(255, 0, 127)),
+            ),
+            "48;2;255;0;127",
+        ),
+        (
+            SelectGraphicRenditionParameter::DefaultBackgroundColorParameter,
+            "49",
+        ),
+        (
+            SelectGraphicRenditionParameter::NotProportionalSpacingParameter,
+            "50",
+        ),
+        (SelectGraphicRenditionParameter::FramedParameter, "51"),
+        (SelectGraphicRenditionParameter::EncircledParameter, "52"),
+        (SelectGraphicRenditionParameter::OverlinedParameter, "53"),
+        // ECMA-48 defines 54 as "not framed, not encircled".
+        (SelectGraphicRenditionParameter::NotFramedParameter, "54"),
+        (SelectGraphicRenditionParameter::NotEncircledParameter, "54"),
+        (SelectGraphicRenditionParameter::NotOverlinedParameter, "55"),
+        (
+            SelectGraphicRenditionParameter::SetUnderlineColorParameter(Color::Color256(196)),
+            "58;5;196",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetUnderlineColorParameter(Color::RGB(255, 0, 0)),
+            "58;2;255;0;0",
+        ),
+        (
+            SelectGraphicRenditionParameter::DefaultUnderlineColorParameter,
+            "59",
+        ),
+        (
+            SelectGraphicRenditionParameter::IdeogramUnderlineParameter,
+            "60",
+        ),
+        (
+            SelectGraphicRenditionParameter::RightSideLineParameter,
+            "60",
+        ),
+        (
+            SelectGraphicRenditionParameter::IdeogramDoubleUnderlineParameter,
+            "61",
+        ),
+        (
+            SelectGraphicRenditionParameter::DoubleLineOnRightSideParameter,
+            "61",
+        ),
+        (
+            SelectGraphicRenditionParameter::IdeogramOverlineParameter,
+            "62",
+        ),
+        (SelectGraphicRenditionParameter::LeftSideLineParameter, "62"),
+        (
+            SelectGraphicRenditionParameter::IdeogramDoubleOverlineParameter,
+            "63",
+        ),
+        (
+            SelectGraphicRenditionParameter::DoubleLineOnLeftSideParameter,
+            "63",
+        ),
+        (
+            SelectGraphicRenditionParameter::IdeogramStressMarkingParameter,
+            "64",
+        ),
+        (
+            SelectGraphicRenditionParameter::IdeogramAttributesOffParameter,
+            "65",
+        ),
+        (SelectGraphicRenditionParameter::SuperscriptParameter, "73"),
+        (SelectGraphicRenditionParameter::SubscriptParameter, "74"),
+        (
+            SelectGraphicRenditionParameter::NotSuperscriptOrSubscriptParameter,
+            "75",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightBlackForeground,
+            ),
+            "90",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightRedForeground,
+            ),
+            "91",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightGreenForeground,
+            ),
+            "92",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightYellowForeground,
+            ),
+            "93",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightBlueForeground,
+            ),
+            "94",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightMagentaForeground,
+            ),
+            "95",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightCyanForeground,
+            ),
+            "96",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightForegroundColorParameter(
+                BrightForegroundColor::BrightWhiteForeground,
+            ),
+            "97",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightBlackBackground,
+            ),
+            "100",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightRedBackground,
+            ),
+            "101",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightGreenBackground,
+            ),
+            "102",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightYellowBackground,
+            ),
+            "103",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightBlueBackground,
+            ),
+            "104",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightMagentaBackground,
+            ),
+            "105",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightCyanBackground,
+            ),
+            "106",
+        ),
+        (
+            SelectGraphicRenditionParameter::SetBrightBackgroundColorParameter(
+                BrightBackgroundColor::BrightWhiteBackground,
+            ),
+            "107",
+        ),
+    ]
+}
+
+#[test]
+fn every_parameter_maps_to_its_documented_code() {
+    for (index, (parameter, expected)) in sgr_table().into_iter().enumerate() {
+        assert_eq!(parameter.to_string(), expected, "table row {}", index);
+    }
+}
+
+#[test]
+fn every_parameter_forms_a_complete_sequence() {
+    for (parameter, expected) in sgr_table() {
+        assert_eq!(
+            EscapeSequence::SelectGraphicRenditionSequence(vec![parameter]).to_string(),
+            format!("\x1b[{}m", expected)
+        );
+    }
+}
+
+#[test]
+fn parameters_are_separated_without_a_trailing_separator() {
+    let sequence = EscapeSequence::SelectGraphicRenditionSequence(vec![
+        SelectGraphicRenditionParameter::BoldParameter,
+        SelectGraphicRenditionParameter::SetForegroundColorParameter(
+            ForegroundColor::ForegroundColor(Color::Color256(44)),
+        ),
+        SelectGraphicRenditionParameter::ItalicParameter,
+    ]);
+    assert_eq!(sequence.to_string(), "\x1b[1;38;5;44;3m");
+}
+
+#[test]
+fn out_of_range_components_are_reduced_like_255() {
+    assert_eq!(
+        Color::RGB(100000, 0, 0).to_color256(),
+        Color::RGB(255, 0, 0).to_color256()
+    );
+    assert_eq!(
+        Color::RGB(u32::MAX, u32::MAX, u32::MAX).to_color256(),
+        Color::Color256(231)
+    );
+    assert_eq!(Color::RGB(u32::MAX, 0, u32::MAX).to_system_color(), 13);
+}
@@ -0,0 +1,221 @@
+use ansi_escape_codes::{BufferSizeError, SixelEncoder};
+
+const HEADER: &str = "\x1bP0;1;0q\"1;1;";
+
+// Reads the output back into the palette (in percent) and one register per pixel.
+fn decode(sequence: &str, width: usize, height: usize) -> (Vec<[u32; 3]>, Vec<Option<usize>>) {
+    let body = sequence
+        .strip_prefix(HEADER)
+        .and_then(|body| body.strip_suffix("\x1b\\"))
+        .expect("sixel introducer and terminator");
+    let body = body
+        .strip_prefix(&format!("{};{}", width, height))
+        .expect("raster attributes");
+    let mut palette = Vec::new();
+    let mut rest = body;
+    // Color definitions are the only commands with a ";2;".
+    while let Some(definition) = rest.strip_prefix('#') {
+        let end = definition
+            .find(|c: char| !c.is_ascii_digit() && c != ';')
+            .unwrap_or(definition.len());
+        let fields: Vec<u32> = definition[..end]
+            .split(';')
+            .map(|field| field.parse().unwrap())
+            .collect();
+        if fields.len() != 5 {
+            break;
+        }
+        assert_eq!(fields[0] as usize, palette.len());
+        assert_eq!(fields[1], 2);
+        palette.push([fields[2], fields[3], fields[4]]);
+        rest = &definition[end..];
+    }
+    let mut pixels = vec![None; width * height];
+    for (band, passes) in rest.split('-').enumerate() {
+        for pass in passes.split('$').filter(|pass| !pass.is_empty()) {
+            let pass = pass.strip_prefix('#').expect("color selection");
+            let digits = pass
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(pass.len());
+            let register: usize = pass[..digits].parse().unwrap();
+            let mut x = 0;
+            let mut chars = pass[digits..].chars().peekable();
+            while let Some(c) = chars.next() {
+                let mut count = 1;
+                let mut sixel = c;
+                if c == '!' {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    count = digits.parse().unwrap();
+                    sixel = chars.next().expect("sixel after a repeat count");
+                }
+                let mask = sixel_mask(sixel);
+                for _ in 0..count {
+                    for row in 0..6 {
+                        if mask & (1 << row) != 0 {
+                            let pixel = (band * 6 + row) * width + x;
+                            assert!(pixels[pixel].is_none(), "pixel drawn twice");
+                            pixels[pixel] = Some(register);
+                        }
+                    }
+                    x += 1;
+                }
+            }
+        }
+    }
+    (palette, pixels)
+}
+
+fn sixel_mask(sixel: char) -> u8 {
+    assert!(('?'..='~').contains(&sixel), "{:?} is not a sixel", sixel);
+    sixel as u8 - 63
+}
+
+fn rgba(pixels: &[[u8; 3]]) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|&[red, green, blue]| vec![red, green, blue, 255])
+        .collect()
+}
+
+#[test]
+fn a_small_image_is_encoded_exactly() {
+    let image = rgba(&[[255, 0, 0], [0, 0, 255], [255, 0, 0]]);
+    assert_eq!(
+        SixelEncoder::new().encode(3, 1, &image),
+        Ok(format!(
+            "{}3;1#0;2;100;0;0#1;2;0;0;100#0@?@$#1?@\x1b\\",
+            HEADER
+        ))
+    );
+}
+
+#[test]
+fn runs_longer_than_three_are_run_length_encoded() {
+    let red = [255, 0, 0];
+    let blue = [0, 0, 255];
+    let mut pixels = vec![red; 3];
+    pixels.extend(vec![blue; 12]);
+    let sequence = SixelEncoder::new().encode(15, 1, &rgba(&pixels)).unwrap();
+    assert!(
+        sequence.ends_with("#0@@@$#1???!12@\x1b\\"),
+        "{:?}",
+        sequence
+    );
+}
+
+#[test]
+fn every_six_rows_start_a_new_band() {
+    let image = rgba(&[[0, 255, 0]; 8]);
+    let sequence = SixelEncoder::new().encode(1, 8, &image).unwrap();
+    assert!(sequence.ends_with("#0~-#0B\x1b\\"), "{:?}", sequence);
+    let (_, pixels) = decode(&sequence, 1, 8);
+    assert_eq!(pixels, vec![Some(0); 8]);
+}
+
+#[test]
+fn transparent_pixels_are_left_out() {
+    let image = [255, 0, 0, 255, 0, 0, 0, 0, 9, 9, 9, 127, 255, 0, 0, 128];
+    let sequence = SixelEncoder::new().encode(2, 2, &image).unwrap();
+    let (palette, pixels) = decode(&sequence, 2, 2);
+    assert_eq!(palette, vec![[100, 0, 0]]);
+    assert_eq!(pixels, vec![Some(0), None, None, Some(0)]);
+    assert_eq!(
+        SixelEncoder::new().encode(2, 1, &[0; 8]),
+        Ok(format!("{}2;1\x1b\\", HEADER))
+    );
+}
+
+#[test]
+fn the_palette_is_limited_to_the_requested_size() {
+    let grays: Vec<[u8; 3]> = (0..=255).map(|value| [value, value, value]).collect();
+    let image = rgba(&grays);
+    for (palette_size, expected) in [(0, 1), (1, 1), (4, 4), (16, 16), (1000, 256)].iter() {
+        let sequence = SixelEncoder::new()
+            .with_palette_size(*palette_size)
+            .encode(256, 1, &image)
+            .unwrap();
+        let (palette, pixels) = decode(&sequence, 256, 1);
+        assert_eq!(palette.len(), *expected);
+        assert!(pixels.iter().all(|pixel| pixel.is_some()));
+    }
+}
+
+#[test]
+fn median_cut_splits_the_widest_channel() {
+    // Two clusters far apart in red and barely apart in green and blue.
+    let mut pixels = vec![[10, 100, 100]; 4];
+    pixels.extend(vec![[12, 104, 100]; 4]);
+    pixels.extend(vec![[240, 100, 102]; 8]);
+    let sequence = SixelEncoder::new()
+        .with_palette_size(2)
+        .encode(16, 1, &rgba(&pixels))
+        .unwrap();
+    let (palette, pixels) = decode(&sequence, 16, 1);
+    assert_eq!(palette, vec![[4, 40, 39], [94, 39, 40]]);
+    assert_eq!(pixels[..8], [Some(0); 8]);
+    assert_eq!(pixels[8..], [Some(1); 8]);
+}
+
+#[test]
+fn dithering_keeps_the_average_of_a_gradient() {
+    let (width, height) = (16, 12);
+    let image: Vec<[u8; 3]> = (0..width * height)
+        .map(|pixel| {
+            let value = (pixel % width * 17) as u8;
+            [value, value, value]
+        })
+        .collect();
+    let encode = |dithering: bool| {
+        let sequence = SixelEncoder::new()
+            .with_palette_size(2)
+            .with_dithering(dithering)
+            .encode(width, height, &rgba(&image))
+            .unwrap();
+        decode(&sequence, width, height)
+    };
+    // How far each column's average drifts from the original.
+    let error = |(palette, pixels): &(Vec<[u32; 3]>, Vec<Option<usize>>)| {
+        (0..width)
+            .map(|x| {
+                let total: u32 = (0..height)
+                    .map(|y| palette[pixels[y * width + x].unwrap()][0] * 255 / 100)
+                    .sum();
+                (total as i32 / height as i32 - (x * 17) as i32).abs()
+            })
+            .sum::<i32>()
+    };
+    let registers_in_column = |pixels: &[Option<usize>], x: usize| {
+        let mut registers: Vec<Option<usize>> =
+            (0..height).map(|y| pixels[y * width + x]).collect();
+        registers.sort();
+        registers.dedup();
+        registers.len()
+    };
+    let plain = encode(false);
+    let dithered = encode(true);
+    assert!(error(&dithered) < error(&plain));
+    // Between the two palette colors the dithered image mixes them.
+    assert_eq!(registers_in_column(&plain.1, 6), 1);
+    assert_eq!(registers_in_column(&dithered.1, 6), 2);
+}
+
+#[test]
+fn a_short_pixel_buffer_is_an_error() {
+    assert_eq!(
+        SixelEncoder::new().encode(2, 2, &[0; 15]),
+        Err(BufferSizeError {
+            expected: 16,
+            actual: 15
+        })
+    );
+    assert_eq!(
+        SixelEncoder::new().encode(usize::MAX, 2, &[]),
+        Err(BufferSizeError {
+            expected: usize::MAX,
+            actual: 0
+        })
+    );
+}
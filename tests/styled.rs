@@ -0,0 +1,24 @@
+use ansi_escape_codes::{Color, Style, StyledString};
+
+#[test]
+fn parsing_and_printing_round_trips() {
+    let input = "plain \x1b[0;1;31mbold red\x1b[0m tail";
+    let styled = StyledString::parse(input);
+    assert_eq!(styled.text(), "plain bold red tail");
+    assert_eq!(styled.spans().len(), 3);
+    assert_eq!(styled.to_string(), input);
+}
+
+#[test]
+fn editing_splits_and_merges_spans() {
+    let mut green = Style::new();
+    green.foreground = Some(Color::Color256(2));
+    let mut styled = StyledString::parse("ab\x1b[1mcd\x1b[0mef");
+    styled.insert(3, green, "XY");
+    assert_eq!(
+        styled.clone().to_string(),
+        "ab\x1b[0;1mc\x1b[0;32mXY\x1b[0;1md\x1b[0mef"
+    );
+    styled.replace_range(2..6, Style::new(), "");
+    assert_eq!(styled.spans(), &[(Style::new(), String::from("abef"))]);
+}
@@ -0,0 +1,42 @@
+use ansi_escape_codes::SvgRenderer;
+
+#[test]
+fn a_small_screen_is_drawn_cell_by_cell() {
+    let svg = SvgRenderer::new()
+        .with_chrome(false)
+        .with_font("mono", 10.0)
+        .with_padding(0.0)
+        .render_output(
+            6,
+            2,
+            "\x1b[1;31m漢x\x1b[0m e\u{301}\r\n\x1b[44m<&>\x1b[4m \x1b[0m",
+        );
+    assert_eq!(
+        svg,
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"36\" height=\"24\" viewBox=\"0 0 36 24\" font-family=\"mono\" font-size=\"10\">\n",
+            "<rect width=\"36\" height=\"24\" rx=\"0\" fill=\"#000000\"/>\n",
+            "<g transform=\"translate(0 0)\">\n",
+            "<text x=\"0\" y=\"9\" textLength=\"18\" lengthAdjust=\"spacingAndGlyphs\" fill=\"#cd0000\" font-weight=\"bold\" xml:space=\"preserve\">漢x</text>\n",
+            "<text x=\"18\" y=\"9\" textLength=\"12\" lengthAdjust=\"spacingAndGlyphs\" fill=\"#e5e5e5\" xml:space=\"preserve\"> e\u{301}</text>\n",
+            "<rect x=\"0\" y=\"12\" width=\"18\" height=\"12\" fill=\"#0000ee\"/>\n",
+            "<rect x=\"18\" y=\"12\" width=\"6\" height=\"12\" fill=\"#0000ee\"/>\n",
+            "<text x=\"0\" y=\"21\" textLength=\"18\" lengthAdjust=\"spacingAndGlyphs\" fill=\"#e5e5e5\" xml:space=\"preserve\">&lt;&amp;&gt;</text>\n",
+            "<text x=\"18\" y=\"21\" textLength=\"6\" lengthAdjust=\"spacingAndGlyphs\" fill=\"#e5e5e5\" text-decoration=\"underline\" xml:space=\"preserve\"> </text>\n",
+            "</g>\n",
+            "</svg>\n"
+        )
+    );
+}
+
+#[test]
+fn the_chrome_draws_buttons_and_an_escaped_title() {
+    let svg = SvgRenderer::new()
+        .with_title("t<")
+        .with_font("mono", 10.0)
+        .render_output(2, 1, "ok");
+    assert!(svg.contains("<rect width=\"44\" height=\"69\" rx=\"8\" fill=\"#000000\"/>\n"));
+    assert_eq!(svg.matches("<circle ").count(), 3);
+    assert!(svg.contains("opacity=\"0.7\">t&lt;</text>\n"));
+    assert!(svg.contains("<g transform=\"translate(16 41)\">\n"));
+}
@@ -0,0 +1,23 @@
+use ansi_escape_codes::{set_tab_stops, EscapeSequence};
+
+#[test]
+fn tab_stops_are_set_and_cleared() {
+    assert_eq!(
+        EscapeSequence::HorizontalTabSetSequence.to_string(),
+        "\x1bH"
+    );
+    assert_eq!(EscapeSequence::ClearTabStopSequence.to_string(), "\x1b[0g");
+    assert_eq!(
+        EscapeSequence::ClearAllTabStopsSequence.to_string(),
+        "\x1b[3g"
+    );
+}
+
+#[test]
+fn columns_become_tab_stops() {
+    assert_eq!(
+        set_tab_stops(&[1, 2, 9]),
+        "\x1b[3g\r\x1bH\r\x1b[C\x1bH\r\x1b[8C\x1bH\r"
+    );
+    assert_eq!(set_tab_stops(&[]), "\x1b[3g\r");
+}
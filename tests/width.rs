@@ -0,0 +1,111 @@
+use ansi_escape_codes::width::clusters;
+use ansi_escape_codes::{char_width, display_width, text_width};
+
+#[test]
+fn single_characters() {
+    let cases = [
+        ('a', 1),
+        (' ', 1),
+        ('~', 1),
+        ('é', 1),
+        ('\0', 0),
+        ('\x07', 0),
+        ('\x1b', 0),
+        ('\n', 0),
+        ('\x7f', 0),
+        ('\u{9b}', 0),
+        ('\u{301}', 0),
+        ('\u{200b}', 0),
+        ('\u{200d}', 0),
+        ('\u{fe0f}', 0),
+        ('漢', 2),
+        ('字', 2),
+        ('あ', 2),
+        ('한', 2),
+        ('\u{3000}', 2),
+        ('Ａ', 2),
+        ('！', 2),
+        ('ｱ', 1),
+        ('😀', 2),
+        ('🚀', 2),
+        ('☺', 1),
+        ('\u{1f1fa}', 1),
+        ('→', 1),
+    ];
+    for (c, width) in cases.iter() {
+        assert_eq!(char_width(*c), *width, "{:?}", c);
+    }
+}
+
+#[test]
+fn text_is_measured_by_cluster() {
+    let cases = [
+        ("", 0),
+        ("hello", 5),
+        ("漢字", 4),
+        ("aＡb", 4),
+        ("e\u{301}", 1),
+        ("a\u{300}\u{301}\u{302}", 1),
+        ("😀", 2),
+        // A family joined with zero width joiners takes two columns, not eight.
+        ("👨\u{200d}👩\u{200d}👧\u{200d}👦", 2),
+        ("🏳\u{fe0f}\u{200d}🌈", 2),
+        // VS16 asks for emoji presentation and VS15 for text.
+        ("☺\u{fe0f}", 2),
+        ("☺\u{fe0e}", 1),
+        ("😀\u{fe0e}", 1),
+        ("👍\u{1f3fd}", 2),
+        ("\u{1f1fa}\u{1f1f8}", 2),
+        ("\u{1f1fa}\u{1f1f8}\u{1f1e9}\u{1f1ea}", 4),
+        ("\u{1f1fa}", 1),
+        ("a\tb", 2),
+    ];
+    for (text, width) in cases.iter() {
+        assert_eq!(text_width(text), *width, "{:?}", text);
+    }
+}
+
+#[test]
+fn escape_sequences_take_no_columns() {
+    let cases = [
+        ("\x1b[1;31mred\x1b[0m", 3),
+        ("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\", 4),
+        ("\x1b]0;title\x07漢", 2),
+        ("\u{9b}31m\u{9b}0m", 0),
+        ("a\r\nb", 2),
+        ("\x1b7x\x1b8", 1),
+        ("\x1b[31m😀\x1b[0m\u{fe0f}", 2),
+    ];
+    for (text, width) in cases.iter() {
+        assert_eq!(display_width(text), *width, "{:?}", text);
+    }
+}
+
+#[test]
+fn clusters_keep_their_parts_together() {
+    let family = "👨\u{200d}👩\u{200d}👧";
+    let flags = "\u{1f1fa}\u{1f1f8}\u{1f1e9}\u{1f1ea}";
+    let cases: Vec<(String, Vec<(&str, usize)>)> = vec![
+        (String::from("ab"), vec![("a", 1), ("b", 1)]),
+        (String::from("e\u{301}x"), vec![("e\u{301}", 1), ("x", 1)]),
+        (format!("{}!", family), vec![(family, 2), ("!", 1)]),
+        (
+            String::from(flags),
+            vec![("\u{1f1fa}\u{1f1f8}", 2), ("\u{1f1e9}\u{1f1ea}", 2)],
+        ),
+        (
+            String::from("☺\u{fe0f}☺\u{fe0e}"),
+            vec![("☺\u{fe0f}", 2), ("☺\u{fe0e}", 1)],
+        ),
+        (String::from("👍\u{1f3fd}"), vec![("👍\u{1f3fd}", 2)]),
+        // A combining mark with nothing before it stands alone.
+        (String::from("\u{301}a"), vec![("\u{301}", 0), ("a", 1)]),
+    ];
+    for (text, expected) in cases.iter() {
+        let expected: Vec<(String, usize)> = expected
+            .iter()
+            .map(|(cluster, width)| (String::from(*cluster), *width))
+            .collect();
+        assert_eq!(clusters(text), expected, "{:?}", text);
+    }
+}
@@ -0,0 +1,41 @@
+use ansi_escape_codes::{wrap_text, TextWrapper};
+
+#[test]
+fn style_continues_on_wrapped_lines() {
+    let lines = wrap_text("The \x1b[1;31mquick brown fox\x1b[0m jumps", 12);
+    assert_eq!(
+        lines,
+        vec![
+            "The \x1b[1;31mquick\x1b[0m",
+            "\x1b[0;1;31mbrown fox\x1b[0m",
+            "jumps",
+        ]
+    );
+}
+
+#[test]
+fn hanging_indent_applies_to_continuation_lines_only() {
+    let wrapper = TextWrapper::new(10).with_hanging_indent(2);
+    assert_eq!(
+        wrapper.fill("one two three\nfour five six"),
+        "one two\n  three\nfour five\n  six"
+    );
+}
+
+#[test]
+fn hanging_indent_leaves_room_for_text() {
+    let lines = TextWrapper::new(4).with_hanging_indent(10).wrap("abcdefgh");
+    assert_eq!(lines, vec!["abcd", "   e", "   f", "   g", "   h"]);
+    for line in TextWrapper::new(1).with_hanging_indent(3).wrap("ab cd") {
+        assert_eq!(line.chars().count(), 1, "{:?}", line);
+    }
+}
+
+#[test]
+fn breaks_between_wide_characters_and_inside_long_words() {
+    assert_eq!(
+        wrap_text("日本語の文章。", 6),
+        vec!["日本語", "の文", "章。"]
+    );
+    assert_eq!(wrap_text("abcdefgh", 3), vec!["abc", "def", "gh"]);
+}